@@ -0,0 +1,218 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use pprint::{
+    bracket, concat, display_width, join, pprint, pprint_batch, smart_join, Doc, Pretty, Printer,
+    WrapAlgo,
+};
+
+fn concat_literals(c: &mut Criterion) {
+    let printer = Printer::default();
+
+    let docs: Vec<_> = (0..10_000).map(|_| Doc::from("ab")).collect();
+    let doc = concat(docs);
+
+    c.bench_function("concat of 10k literals", |b| {
+        b.iter(|| pprint(black_box(&doc), &printer))
+    });
+}
+
+fn batch(c: &mut Criterion) {
+    let printer = Printer::default();
+
+    let docs: Vec<_> = (0..1000)
+        .map(|i| Doc::from(vec![(0..100).map(|j| i * j).collect::<Vec<_>>(); 4]))
+        .collect();
+
+    c.bench_function("1000 documents sequentially", |b| {
+        b.iter(|| {
+            black_box(&docs)
+                .iter()
+                .map(|doc| pprint(doc, &printer))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    c.bench_function("1000 documents batched", |b| {
+        b.iter(|| pprint_batch(black_box(&docs), &printer))
+    });
+}
+
+fn compiled(c: &mut Criterion) {
+    let printers: Vec<_> = [20, 40, 60, 80, 120]
+        .into_iter()
+        .map(|max_width| Printer {
+            max_width,
+            ..Printer::default()
+        })
+        .collect();
+
+    // Deeply nested, so that measuring each group re-measures much of the document
+    let doc = (0..8).fold(Doc::from("leaf"), |doc, _| Doc::from(vec![doc; 3]));
+
+    c.bench_function("one document at 5 widths", |b| {
+        b.iter(|| {
+            printers
+                .iter()
+                .map(|printer| pprint(black_box(&doc), printer))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let compiled = doc.clone().compile();
+
+    c.bench_function("one compiled document at 5 widths", |b| {
+        b.iter(|| {
+            printers
+                .iter()
+                .map(|printer| black_box(&compiled).render(printer))
+                .collect::<Vec<_>>()
+        })
+    });
+}
+
+/// pprint is meant to beat `format!("{:#?}", ...)` here, and doesn't yet: in one run,
+/// format! took ~1.0ms, and pprint ~1.5ms, down from ~2.0ms; most of what's left is
+/// converting the values, measuring each row for its smart join, and justifying it.
+fn integers(c: &mut Criterion) {
+    let printer = Printer::default();
+
+    let values: Vec<Vec<usize>> = (0..100)
+        .map(|i| (0..100).map(|j| i * j * 1000).collect())
+        .collect();
+
+    c.bench_function("100x100 integers with format!", |b| {
+        b.iter(|| format!("{:#?}", black_box(&values)))
+    });
+
+    c.bench_function("100x100 integers with pprint", |b| {
+        b.iter(|| printer.pprint(black_box(&values).clone()))
+    });
+}
+
+fn integer_widths(c: &mut Criterion) {
+    let printer = Printer::default();
+    let doc = Doc::from((0..100_000).collect::<Vec<i64>>());
+
+    c.bench_function("display_width of 100k integers", |b| {
+        b.iter(|| display_width(black_box(&doc), &printer))
+    });
+    c.bench_function("100k integers with pprint", |b| {
+        b.iter(|| pprint(black_box(&doc), &printer))
+    });
+}
+
+fn join_hardline(c: &mut Criterion) {
+    let printer = Printer::default();
+
+    let docs: Vec<_> = (0..10_000)
+        .map(|i| Doc::from(format!("item{}", i)))
+        .collect();
+    let doc = join(Doc::from(", ") + Doc::Hardline, docs);
+
+    c.bench_function("join of 10k on a comma and hardline", |b| {
+        b.iter(|| pprint(black_box(&doc), &printer))
+    });
+}
+
+fn wrap_algorithm(c: &mut Criterion) {
+    let doc = smart_join(", ", (0..10_000).collect());
+
+    for (name, wrap_algorithm) in [
+        (
+            "smart join of 10k with minimum raggedness",
+            WrapAlgo::MinRaggedness,
+        ),
+        ("smart join of 10k with a greedy fill", WrapAlgo::Greedy),
+    ] {
+        let printer = Printer {
+            wrap_algorithm,
+            ..Printer::default()
+        };
+        c.bench_function(name, |b| b.iter(|| pprint(black_box(&doc), &printer)));
+    }
+}
+
+// The same many-variant enum, with and without its unit variants' text cached
+macro_rules! opcodes {
+    ($($name:ident $(#[$attr:meta])*),*) => {
+        $(
+            #[derive(Pretty, Clone, Copy)]
+            #[pprint(discriminant)]
+            $(#[$attr])*
+            #[allow(dead_code)]
+            enum $name {
+                Nop, Load, Store, Add, Sub, Mul, Div, Rem, And, Or, Xor, Shl, Shr, Neg, Not, Eq,
+                Ne, Lt, Le, Gt, Ge, Jump, JumpIf, Call, Ret, Push, Pop, Dup, Swap, Halt,
+            }
+        )*
+    };
+}
+opcodes!(Opcode, CachedOpcode #[pprint(cache_units)]);
+
+fn unit_variants(c: &mut Criterion) {
+    let opcodes = [Opcode::Mul, Opcode::JumpIf, Opcode::Halt, Opcode::Nop];
+    let cached = [
+        CachedOpcode::Mul,
+        CachedOpcode::JumpIf,
+        CachedOpcode::Halt,
+        CachedOpcode::Nop,
+    ];
+
+    c.bench_function("10k unit variants", |b| {
+        b.iter(|| {
+            (0..10_000)
+                .map(|i| Doc::from(black_box(opcodes[i % 4])))
+                .collect::<Vec<_>>()
+        })
+    });
+    c.bench_function("10k unit variants with cache_units", |b| {
+        b.iter(|| {
+            (0..10_000)
+                .map(|i| Doc::from(black_box(cached[i % 4])))
+                .collect::<Vec<_>>()
+        })
+    });
+}
+
+fn tiny_vectors(c: &mut Criterion) {
+    let printer = Printer::default();
+    let doc = Doc::from(
+        (0..10_000)
+            .map(|i| vec![i, i + 1, i + 2])
+            .collect::<Vec<_>>(),
+    );
+
+    c.bench_function("10k vectors of 3 integers", |b| {
+        b.iter(|| pprint(black_box(&doc), &printer))
+    });
+}
+
+fn doc_vectors(c: &mut Criterion) {
+    let docs = || (0..10_000).map(Doc::from).collect::<Vec<_>>();
+
+    c.bench_function("Doc::from of a Vec of 10k docs", |b| {
+        b.iter_batched(docs, Doc::from, BatchSize::SmallInput)
+    });
+    // The same document, built without converting each element
+    c.bench_function("Doc::SmartJoin of 10k docs, built directly", |b| {
+        b.iter_batched(
+            docs,
+            |docs| bracket("[", Doc::SmartJoin(Box::new(Doc::from(", ")), docs), "]"),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(
+    benches,
+    concat_literals,
+    batch,
+    compiled,
+    integers,
+    integer_widths,
+    join_hardline,
+    wrap_algorithm,
+    unit_variants,
+    tiny_vectors,
+    doc_vectors
+);
+criterion_main!(benches);
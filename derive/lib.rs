@@ -7,21 +7,33 @@ use syn::{
     Meta, NestedMeta, Variant, WherePredicate,
 };
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Default)]
 struct PrettyAttributes {
     // Field: Skip this field - don't include it in the output
     skip: bool,
     // Field: Indent this field - add a newline and indent before and after
     indent: bool,
     // Field: Rename this field - use the given string as the field name
-    rename: Option<String>,
+    rename: Option<syn::LitStr>,
     // Field: Use the given function to get the value of this field
-    getter: Option<String>,
+    getter: Option<syn::LitStr>,
     // Container: Verbose output - include field names in output
     verbose: bool,
+    // Container: Output format - "yaml" renders block-style `key: value` lines
+    format: Option<String>,
+    // Container: Render an enum's unit variants with their discriminant, `Name = value`
+    discriminant: bool,
+    // Container: Format each unit variant's discriminant text once, in a static
+    cache_units: bool,
 }
 
-fn parse_pprint_attrs(attrs: &[Attribute]) -> PrettyAttributes {
+impl PrettyAttributes {
+    fn is_yaml(&self) -> bool {
+        self.format.as_deref() == Some("yaml")
+    }
+}
+
+fn parse_pprint_attrs(attrs: &[Attribute]) -> syn::Result<PrettyAttributes> {
     let mut pprint_attr = PrettyAttributes::default();
 
     for meta in attrs
@@ -43,12 +55,25 @@ fn parse_pprint_attrs(attrs: &[Attribute]) -> PrettyAttributes {
                 // Parse the attribute name and value
                 if nested_meta.path().is_ident("rename") {
                     if let Lit::Str(rename) = &_name_value.lit {
-                        pprint_attr.rename = Some(rename.value());
+                        pprint_attr.rename = Some(rename.clone());
                     }
                 }
                 if nested_meta.path().is_ident("getter") {
                     if let Lit::Str(getter) = &_name_value.lit {
-                        pprint_attr.getter = Some(getter.value());
+                        pprint_attr.getter = Some(getter.clone());
+                    }
+                }
+                if nested_meta.path().is_ident("format") {
+                    if let Lit::Str(format) = &_name_value.lit {
+                        match format.value().as_str() {
+                            "yaml" => pprint_attr.format = Some(format.value()),
+                            other => {
+                                return Err(syn::Error::new_spanned(
+                                    format,
+                                    format!("unsupported pprint format: {:?}", other),
+                                ))
+                            }
+                        }
                     }
                 }
             } else {
@@ -57,12 +82,14 @@ fn parse_pprint_attrs(attrs: &[Attribute]) -> PrettyAttributes {
                     path if path.is_ident("skip") => pprint_attr.skip = true,
                     path if path.is_ident("indent") => pprint_attr.indent = true,
                     path if path.is_ident("verbose") => pprint_attr.verbose = true,
+                    path if path.is_ident("discriminant") => pprint_attr.discriminant = true,
+                    path if path.is_ident("cache_units") => pprint_attr.cache_units = true,
                     _ => {}
                 }
             }
         }
     }
-    pprint_attr
+    Ok(pprint_attr)
 }
 
 fn apply_pprint_doc_attributes(
@@ -97,11 +124,18 @@ fn apply_pprint_doc_attributes(
 pub fn pprint_derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    let pprint_container_attrs = parse_pprint_attrs(&input.attrs);
+    // Unsupported input is reported as a compile error spanned at the offending item
+    expand_pprint_derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_pprint_derive(input: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
+    let pprint_container_attrs = parse_pprint_attrs(&input.attrs)?;
 
     let name = &input.ident;
     let generics = &input.generics;
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = generics.split_for_impl();
 
     // A Doc needs a lifetime - if the user has specified one, use it, otherwise use 'a
     let doc_lifetime = match generics.lifetimes().next() {
@@ -109,14 +143,26 @@ pub fn pprint_derive(input: TokenStream) -> TokenStream {
         None => parse_quote!('a),
     };
 
+    // If the Doc lifetime isn't one of the user's, the impl needs to declare it
+    let mut impl_generics = generics.clone();
+    if generics.lifetimes().next().is_none() {
+        impl_generics.params.insert(0, parse_quote!(#doc_lifetime));
+    }
+    let (impl_generics, _, _) = impl_generics.split_for_impl();
+
     let doc_match = match &input.data {
         Data::Struct(data_struct) => {
-            generate_struct_match(name, &data_struct.fields, &pprint_container_attrs)
+            generate_struct_match(name, &data_struct.fields, &pprint_container_attrs)?
         }
         Data::Enum(data_enum) => {
-            generate_enum_match(name, &data_enum.variants, &pprint_container_attrs)
+            generate_enum_match(name, &data_enum.variants, &pprint_container_attrs)?
+        }
+        Data::Union(data_union) => {
+            return Err(syn::Error::new_spanned(
+                data_union.union_token,
+                "Pretty can only be derived for structs and enums",
+            ))
         }
-        _ => panic!("Only structs and enums are supported."),
     };
 
     // If there's a where clause extant, we want to preserve it, else we want to create a new one
@@ -127,7 +173,7 @@ pub fn pprint_derive(input: TokenStream) -> TokenStream {
     // Every generic type needs to be constrained to Into<Doc<'a>>
     let new_generic_predicates = generics.type_params().map(|tp| -> WherePredicate {
         let ident = &tp.ident;
-        parse_quote! { #ident : Into<Doc<#doc_lifetime>> }
+        parse_quote! { #ident : Into<pprint::Doc<#doc_lifetime>> }
     });
     // Every lifetime needs to be constrained to 'a
     let new_lifetime_predicates = generics.lifetimes().map(|lt| -> WherePredicate {
@@ -144,45 +190,121 @@ pub fn pprint_derive(input: TokenStream) -> TokenStream {
             #new_where_clause
         {
             fn from(_self: #name #ty_generics) -> Self {
-                use pprint::{concat, indent, wrap, join, Doc, Join, Wrap, Group, Indent, Dedent};
+                use pprint::{concat, indent, wrap, join, Doc, Join, SmartJoin, Wrap, Group, Indent, Dedent, Spacing};
                 #doc_match
             }
         }
     };
 
-    TokenStream::from(expanded)
+    Ok(expanded)
 }
 
-fn generate_struct_fields_match(fields: &Fields) -> Vec<proc_macro2::TokenStream> {
-    let format_key_value = |field_ident: &Option<syn::Ident>, field: &Field| {
-        let pprint_attr = parse_pprint_attrs(&field.attrs);
-        if pprint_attr.skip {
-            return None;
+fn is_type_named(ty: &syn::Type, name: &str) -> bool {
+    match ty {
+        syn::Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == name)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+fn is_vec_type(ty: &syn::Type) -> bool {
+    is_type_named(ty, "Vec")
+}
+
+fn is_phantom_type(ty: &syn::Type) -> bool {
+    is_type_named(ty, "PhantomData")
+}
+
+/// Tuple fields are printed bare, without a name, so renaming one would do nothing.
+fn tuple_field_renamed(rename: &syn::LitStr) -> syn::Error {
+    syn::Error::new_spanned(rename, "rename is not supported on tuple fields")
+}
+
+/// How the `i`th field of a struct is accessed, by its name or, in a tuple struct, its index.
+fn field_member(i: usize, field: &Field) -> syn::Member {
+    match &field.ident {
+        Some(ident) => syn::Member::Named(ident.clone()),
+        None => syn::Member::Unnamed(syn::Index::from(i)),
+    }
+}
+
+fn generate_struct_fields_match(
+    fields: &Fields,
+    pprint_container_attrs: &PrettyAttributes,
+) -> syn::Result<Vec<proc_macro2::TokenStream>> {
+    let format_key_value = |member: syn::Member, field: &Field| {
+        let pprint_attr = parse_pprint_attrs(&field.attrs)?;
+        // Phantom fields have nothing to show
+        if pprint_attr.skip || is_phantom_type(&field.ty) {
+            return Ok(None);
         }
-        let field_name = pprint_attr.rename.clone().unwrap_or_else(|| {
-            field_ident
-                .as_ref()
-                .map(|ident| ident.to_string())
-                .unwrap_or_else(|| "".to_string())
-        });
+
+        // If the field has a getter, we need to call it to get the value of the field
+        let value = match &pprint_attr.getter {
+            Some(getter) => {
+                let getter = getter.parse::<syn::Expr>()?;
+                quote! { #getter(&_self.#member) }
+            }
+            None => quote! { _self.#member },
+        };
 
         let is_generic_type = matches!(field.ty, syn::Type::Path(_));
-        // If the type is a generic type, we need to call into() on it to convert it to a Doc
-        let field_doc = if is_generic_type {
-            quote! { _self.#field_ident.into() }
+        let field_doc = if pprint_container_attrs.is_yaml()
+            && is_vec_type(&field.ty)
+            && pprint_attr.getter.is_none()
+        {
+            // Sequences are rendered as one "- item" per line
+            quote! { pprint::block_seq(#value) }
+        } else if is_generic_type || pprint_attr.getter.is_some() {
+            // If the type is a generic type, or the getter's unknown, we need to call into()
+            // on it to convert it to a Doc
+            quote! { Into::<Doc>::into(#value) }
         } else {
-            quote! { Doc::from(_self.#field_ident) }
+            quote! { Doc::from(#value) }
         };
+        let value_doc = field_doc.clone();
         let field_doc = apply_pprint_doc_attributes(&field_doc, &pprint_attr);
+
+        // A tuple struct's fields are printed bare, as a tuple's are
+        let field_name = match (&pprint_attr.rename, &member) {
+            (Some(rename), syn::Member::Named(_)) => rename.value(),
+            (None, syn::Member::Named(ident)) => ident.to_string(),
+            (Some(rename), syn::Member::Unnamed(_)) => return Err(tuple_field_renamed(rename)),
+            (None, syn::Member::Unnamed(_)) => return Ok(Some(field_doc)),
+        };
+
+        if pprint_container_attrs.is_yaml() && pprint_attr.indent {
+            // Doc of the form: "key:" followed by a block indented a level further, every
+            // line of it, as YAML requires of e.g. a sequence's "- item"s
+            return Ok(Some(quote! {
+                concat(vec![
+                    Doc::from(#field_name),
+                    Doc::from(":"),
+                    indent(indent(Doc::Hardline + #value_doc)),
+                ])
+            }));
+        }
+        if pprint_container_attrs.is_yaml() {
+            // Doc of the form: "key: value", or "key:" followed by an indented block
+            return Ok(Some(quote! {
+                pprint::block_entry(#field_name, #value_doc)
+            }));
+        }
+
         let field_doc = quote! {
             concat(vec![
                 Doc::from(#field_name),
-                Doc::from(": "),
+                Doc::from(":"),
+                Doc::Space(Spacing::AfterColon),
                 #field_doc,
             ])
         };
         // Doc of the form: "key: value"
-        Some(field_doc)
+        Ok(Some(field_doc))
     };
 
     // Generate the match arms for each field
@@ -190,22 +312,18 @@ fn generate_struct_fields_match(fields: &Fields) -> Vec<proc_macro2::TokenStream
         Fields::Named(fields) => fields
             .named
             .iter()
-            .filter_map(|field| {
-                let field_ident = &field.ident;
-                format_key_value(field_ident, field)
-            })
+            .map(|field| format_key_value(field_member(0, field), field))
+            .filter_map(syn::Result::transpose)
             .collect(),
-        // If it's unnamed, we need to generate a field name for each field
+        // Tuple struct fields are accessed by index, `_self.0`
         Fields::Unnamed(fields) => fields
             .unnamed
             .iter()
             .enumerate()
-            .filter_map(|(i, field)| {
-                let field_ident = Some(format_ident!("field_{}", i));
-                format_key_value(&field_ident, field)
-            })
+            .map(|(i, field)| format_key_value(field_member(i, field), field))
+            .filter_map(syn::Result::transpose)
             .collect(),
-        Fields::Unit => vec![],
+        Fields::Unit => Ok(vec![]),
     }
 }
 
@@ -213,32 +331,90 @@ fn generate_struct_match(
     ident: &syn::Ident,
     fields: &Fields,
     pprint_container_attrs: &PrettyAttributes,
-) -> proc_macro2::TokenStream {
+) -> syn::Result<proc_macro2::TokenStream> {
     let name = pprint_container_attrs
         .rename
-        .clone()
+        .as_ref()
+        .map(syn::LitStr::value)
         .unwrap_or_else(|| ident.to_string());
 
-    let fields_match = generate_struct_fields_match(fields);
+    let fields_match = generate_struct_fields_match(fields, pprint_container_attrs)?;
 
     // TODO: Fix: hack to remove the unused variable warning when the field is ignored.
-    let named_fields = fields.into_iter().filter_map(|field| field.ident.clone());
+    let named_fields = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| field_member(i, field));
 
-    match fields {
-        Fields::Named(_) | Fields::Unnamed(_) => {
-            let body = quote! {
-                vec![#(#fields_match,)*]
-                        .join(Doc::from(", ") + Doc::Hardline)
-                        .group()
-                        .wrap("{", Doc::from("}").dedent())
-                        .indent()
+    Ok(match fields {
+        // A tuple struct prints as a tuple, or, with a single field, as just that field,
+        // as tuple enum variants do; in YAML, as a sequence
+        Fields::Unnamed(_) => {
+            let body = match fields_match.as_slice() {
+                [] => quote! { pprint::empty("(", ")") },
+                [field_doc] => quote! { #field_doc },
+                _ if pprint_container_attrs.is_yaml() => {
+                    quote! { pprint::block_seq(vec![#(#fields_match,)*]) }
+                }
+                _ => quote! {
+                    pprint::delimit("(", vec![#(#fields_match,)*].smart_join(", "), ")", Some(false))
+                },
             };
-            let header = quote! {
-                Doc::from(format!("{} ", #name)).group().indent()
+            let doc_match = match () {
+                _ if !pprint_container_attrs.verbose => body,
+                _ if pprint_container_attrs.is_yaml() => {
+                    quote! { pprint::block_entry(#name, #body) }
+                }
+                // Several fields are already printed with their own parentheses
+                _ if fields_match.len() > 1 => quote! { Doc::from(#name) + #body },
+                _ => quote! {
+                    concat(vec![Doc::from(#name), Doc::from("("), #body, Doc::from(")")])
+                },
+            };
+            quote! {
+                (#((&_self.#named_fields),)*);
+                #doc_match
+            }
+        }
+        Fields::Named(_) if pprint_container_attrs.is_yaml() => {
+            let body = if fields_match.is_empty() {
+                quote! { pprint::empty("{", "}") }
+            } else {
+                quote! { vec![#(#fields_match,)*].join(Doc::Hardline) }
+            };
+            let doc_match = if pprint_container_attrs.verbose {
+                quote! { pprint::block_entry(#name, #body) }
+            } else {
+                body
+            };
+            quote! {
+                (#((&_self.#named_fields),)*);
+                #doc_match
+            }
+        }
+        Fields::Named(_) => {
+            let body = match () {
+                _ if fields_match.is_empty() => quote! { pprint::empty("{", "}") },
+                // Behind a header, fields stay on one line if they fit, otherwise each gets
+                // its own line
+                _ if pprint_container_attrs.verbose => quote! {
+                    pprint::bracket(
+                        "{",
+                        vec![#(#fields_match,)*].join(Doc::from(",") + pprint::group_line(" ")),
+                        "}",
+                    )
+                },
+                _ => quote! {
+                    (Doc::Space(Spacing::InsideBrackets) + vec![#(#fields_match,)*]
+                            .join(Doc::from(", ") + Doc::Hardline))
+                            .group()
+                            .wrap("{", Doc::Space(Spacing::InsideBrackets) + Doc::from("}").dedent())
+                            .indent()
+                },
             };
             let doc_match = if pprint_container_attrs.verbose {
                 quote! {
-                    concat(vec![#header, #body])
+                    concat(vec![Doc::from(#name), Doc::from(" "), #body])
                 }
             } else {
                 body
@@ -250,35 +426,49 @@ fn generate_struct_match(
                 #doc_match
             }
         }
+        // A unit struct renders as just its name, as unit enum variants do
         Fields::Unit => {
             quote! {
-                Doc::from(stringify!(#ident))
+                Doc::from(#name)
             }
         }
-    }
+    })
 }
 
 fn generate_variants_match(
     variant: &syn::Variant,
     constructor: &proc_macro2::TokenStream,
+    discriminant: Option<proc_macro2::TokenStream>,
     pprint_container_attrs: &PrettyAttributes,
-) -> Option<proc_macro2::TokenStream> {
-    let pprint_attr = parse_pprint_attrs(&variant.attrs);
+) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    let pprint_attr = parse_pprint_attrs(&variant.attrs)?;
 
     if pprint_attr.skip {
-        return None;
+        return Ok(None);
     }
 
     let variant_name = pprint_attr
         .rename
-        .clone()
+        .as_ref()
+        .map(syn::LitStr::value)
         .unwrap_or_else(|| variant.ident.to_string());
 
+    if let Fields::Unnamed(fields) = &variant.fields {
+        for field in &fields.unnamed {
+            if let Some(rename) = parse_pprint_attrs(&field.attrs)?.rename {
+                return Err(tuple_field_renamed(&rename));
+            }
+        }
+    }
+
     let field_bindings = match &variant.fields {
         Fields::Named(fields) => fields
             .named
             .iter()
-            .map(|field| quote! { #field.ident })
+            .map(|field| {
+                let ident = &field.ident;
+                quote! { #ident }
+            })
             .collect(),
         // If it's unnamed (most variant fields are), we need to generate a field name for each field
         Fields::Unnamed(fields) => fields
@@ -305,27 +495,50 @@ fn generate_variants_match(
     };
 
     // If the variant has a getter, we need to call it to get the value of the field
-    let field_doc = match pprint_attr.getter.clone() {
+    let field_doc = match &pprint_attr.getter {
         Some(getter) => {
-            let getter = syn::parse_str::<syn::Expr>(&getter).unwrap();
+            let getter = getter.parse::<syn::Expr>()?;
             quote! {
                 #getter(&#field_bindings_tup)
             }
         }
         None => field_bindings_tup,
     };
-    let field_doc = quote! {
-        Doc::from(#field_doc)
+    let field_doc = match discriminant {
+        // A unit variant's text never changes, so it can be formatted just once, then
+        // borrowed, rather than building and joining its parts on each conversion
+        Some(discriminant) if pprint_container_attrs.cache_units => quote! {{
+            static TEXT: ::std::sync::OnceLock<String> = ::std::sync::OnceLock::new();
+            Doc::from(
+                TEXT.get_or_init(|| format!("{} = {}", #variant_name, #discriminant))
+                    .as_str(),
+            )
+        }},
+        Some(discriminant) => quote! {
+            Doc::from(#variant_name) + Doc::from(" = ") + Doc::from(#discriminant)
+        },
+        None => quote! {
+            Doc::from(#field_doc)
+        },
     };
     let field_doc = apply_pprint_doc_attributes(&field_doc, &pprint_attr);
     // If in verbose mode, we need to wrap the field doc in a tuple,
     // but not if the variant has no fields
-    let field_doc = if pprint_container_attrs.verbose && !matches!(variant.fields, Fields::Unit) {
+    let field_doc = if pprint_container_attrs.is_yaml() && !matches!(variant.fields, Fields::Unit) {
+        quote! {
+            pprint::block_entry(#variant_name, #field_doc)
+        }
+    } else if pprint_container_attrs.verbose && !matches!(variant.fields, Fields::Unit) {
+        // Several fields are already printed as a tuple, with its own parentheses
+        let field_doc = if field_bindings.len() == 1 || pprint_attr.getter.is_some() {
+            quote! { Doc::from(#field_doc).wrap("(", ")") }
+        } else {
+            field_doc
+        };
         quote! {
             concat(vec![
                 Doc::from(#variant_name),
-                Doc::from(#field_doc)
-                .wrap("(", ")")
+                #field_doc
             ])
         }
     } else {
@@ -350,26 +563,50 @@ fn generate_variants_match(
             }
         }
     };
-    Some(match_arms)
+    Ok(Some(match_arms))
 }
 
 fn generate_enum_match(
     name: &syn::Ident,
     variants: &syn::punctuated::Punctuated<Variant, Comma>,
     pprint_container_attrs: &PrettyAttributes,
-) -> proc_macro2::TokenStream {
+) -> syn::Result<proc_macro2::TokenStream> {
+    let fieldless = variants
+        .iter()
+        .all(|variant| matches!(variant.fields, Fields::Unit));
+
     let format_variant = |variant: &Variant| {
         let variant_ident = &variant.ident;
         let constructor = quote! { #name::#variant_ident };
-        generate_variants_match(variant, &constructor, pprint_container_attrs)
+
+        // A fieldless enum's discriminants, implicit or not, can be read with a cast;
+        // otherwise, only explicit discriminants are known
+        let discriminant = match &variant.discriminant {
+            _ if !pprint_container_attrs.discriminant => None,
+            _ if !matches!(variant.fields, Fields::Unit) => None,
+            _ if fieldless => Some(quote! { #constructor as i128 }),
+            Some((_, expr)) => Some(quote! { #expr }),
+            None => {
+                return Err(syn::Error::new_spanned(
+                    variant,
+                    "discriminant requires an explicit discriminant on each unit variant \
+                     of an enum with fields",
+                ))
+            }
+        };
+        generate_variants_match(variant, &constructor, discriminant, pprint_container_attrs)
     };
-    let variants_match = variants.into_iter().filter_map(format_variant);
+    let variants_match = variants
+        .into_iter()
+        .map(format_variant)
+        .filter_map(syn::Result::transpose)
+        .collect::<syn::Result<Vec<_>>>()?;
 
-    quote! {
+    Ok(quote! {
         match _self {
            #(#variants_match,)*
            // Ensure no variant is missing
            _ => Doc::Null
         }
-    }
+    })
 }
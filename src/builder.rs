@@ -0,0 +1,64 @@
+use crate::doc::{indent, Doc};
+use std::borrow::Cow;
+
+/// Builds a document imperatively, as an alternative to the functional combinators:
+/// push text and line breaks in order, and open indented scopes with closures.
+///
+/// ```
+/// use pprint::{concat, indent, Doc, DocBuilder};
+///
+/// let doc = DocBuilder::new()
+///     .text("fn main() {")
+///     .indent(|b| {
+///         b.line().text("run();");
+///     })
+///     .line()
+///     .text("}")
+///     .build();
+///
+/// let expected = concat(vec![
+///     Doc::from("fn main() {"),
+///     indent(concat(vec![Doc::Hardline, Doc::from("run();")])),
+///     Doc::Hardline,
+///     Doc::from("}"),
+/// ]);
+/// assert_eq!(doc, expected);
+/// ```
+#[derive(Debug, Default)]
+pub struct DocBuilder<'a> {
+    docs: Vec<Doc<'a>>,
+}
+
+impl<'a> DocBuilder<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append text.
+    pub fn text(&mut self, s: impl Into<Cow<'a, str>>) -> &mut Self {
+        self.doc(Doc::String(s.into()))
+    }
+
+    /// Append any document.
+    pub fn doc(&mut self, doc: impl Into<Doc<'a>>) -> &mut Self {
+        self.docs.push(doc.into());
+        self
+    }
+
+    /// Append an absolute line break, indented to the current scope.
+    pub fn line(&mut self) -> &mut Self {
+        self.doc(Doc::Hardline)
+    }
+
+    /// Append the documents built by `f`, indented by one level.
+    pub fn indent(&mut self, f: impl FnOnce(&mut DocBuilder<'a>)) -> &mut Self {
+        let mut scope = DocBuilder::new();
+        f(&mut scope);
+        self.doc(indent(scope.build()))
+    }
+
+    /// Take the documents built so far, leaving the builder empty.
+    pub fn build(&mut self) -> Doc<'a> {
+        Doc::Concat(std::mem::take(&mut self.docs))
+    }
+}
@@ -0,0 +1,68 @@
+use crate::doc::Doc;
+use crate::print::{measure_nodes, pprint_in, CollapseStyle, Printer, Widths, WrapAlgo};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// The printer options that a document's flat widths depend on.
+type MeasureKey = (
+    usize,
+    usize,
+    bool,
+    bool,
+    bool,
+    Option<String>,
+    WrapAlgo,
+    bool,
+    CollapseStyle,
+);
+
+fn measure_key(printer: &Printer) -> MeasureKey {
+    (
+        printer.max_width,
+        printer.indent,
+        printer.ascii_only,
+        printer.space_after_colon,
+        printer.space_inside_brackets,
+        printer.null_display.as_deref().map(str::to_owned),
+        printer.wrap_algorithm,
+        printer.exact_widths,
+        printer.empty_collection,
+    )
+}
+
+/// A document prepared for repeated rendering, e.g. at several page widths.
+/// The flat width of every node is measured once per printer configuration and cached,
+/// so rendering again only re-runs the printer, rather than re-measuring each group.
+pub struct CompiledDoc<'a> {
+    // Boxed so that the root's address, which keys its cached width, is stable
+    doc: Box<Doc<'a>>,
+    widths: RefCell<HashMap<MeasureKey, Widths>>,
+}
+
+impl<'a> CompiledDoc<'a> {
+    /// Render the document, identically to `pprint`.
+    pub fn render(&self, printer: &Printer) -> String {
+        let mut cache = self.widths.borrow_mut();
+        let widths = cache.entry(measure_key(printer)).or_insert_with(|| {
+            let mut widths = Widths::new();
+            measure_nodes(&self.doc, printer, &mut widths);
+            widths
+        });
+        pprint_in(&self.doc, printer, Some(widths))
+    }
+
+    /// The compiled document.
+    pub fn doc(&self) -> &Doc<'a> {
+        &self.doc
+    }
+}
+
+impl<'a> Doc<'a> {
+    /// Prepare the document for repeated rendering; see `CompiledDoc`.
+    pub fn compile(self) -> CompiledDoc<'a> {
+        CompiledDoc {
+            doc: Box::new(self),
+            widths: RefCell::new(HashMap::new()),
+        }
+    }
+}
@@ -1,6 +1,22 @@
 use std::{
     borrow::Cow,
+    cell::{Cell, RefCell},
+    cmp::Ordering as CmpOrdering,
     collections::{HashMap, HashSet},
+    ffi::{OsStr, OsString},
+    fmt::{Alignment, Arguments},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    net::Shutdown,
+    num::{
+        FpCategory, NonZeroI128, NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize,
+        NonZeroU128, NonZeroU16, NonZeroU32, NonZeroU64, NonZeroU8, NonZeroUsize, Saturating,
+        Wrapping,
+    },
+    ops::{Bound, ControlFlow, Range, RangeInclusive},
+    rc::Rc,
+    sync::{atomic::Ordering as AtomicOrdering, Arc, Mutex, OnceLock, PoisonError, TryLockError},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use regex::Regex;
@@ -8,27 +24,255 @@ use regex::Regex;
 /// A document that can be pretty printed.
 /// This is the core type of the library.
 /// It is an enum that represents the different ways a document can be printed.
+///
+/// Equality, hashing, and ordering are derived, and so are structural: two documents
+/// are equal only if their trees are identical, and documents are ordered first by
+/// variant, in declaration order (`Null < String < Int < ...`), then by their contents.
+/// To compare documents by what they print instead, see [`Doc::structural_eq`].
 #[derive(Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Doc<'a> {
     Null,
     String(Cow<'a, str>),
+    // Integers are stored unformatted, and written straight into the output
+    Int(i128),
+    UInt(u128),
+    Lazy(LazyText<'a>),
 
     Concat(Vec<Doc<'a>>),
 
     Group(Box<Doc<'a>>),
+    // Alternative layouts, of which the first to fit is printed; see `conditional_group`
+    ConditionalGroup(Vec<Doc<'a>>),
 
     Indent(Box<Doc<'a>>),
     Dedent(Box<Doc<'a>>),
+    // Indented by the given step, rather than the printer's
+    IndentBy(usize, Box<Doc<'a>>),
 
     Join(Box<Doc<'a>>, Vec<Doc<'a>>),
     SmartJoin(Box<Doc<'a>>, Vec<Doc<'a>>),
+    SmartJoinMax(Box<Doc<'a>>, Vec<Doc<'a>>, usize),
+    // A join of an unordered collection's entries, sorted if the printer sorts keys
+    Unordered(Box<Doc<'a>>),
 
     IfBreak(Box<Doc<'a>>, Box<Doc<'a>>),
+    // Flat, then broken, as decided by the outermost enclosing group
+    IfBreakRoot(Box<Doc<'a>>, Box<Doc<'a>>),
+
+    Table(Vec<Vec<Doc<'a>>>),
+
+    Prefix(Cow<'a, str>, Box<Doc<'a>>),
+    // Padded with spaces to a width, measured as it's printed; see `pad_left`
+    Pad(PadSide, usize, Box<Doc<'a>>),
 
     Hardline,
     Softline,
     Mediumline,
     Line,
+    GroupLine(Box<Doc<'a>>),
+    // Between an empty collection's brackets, breaking only if the printer expands them
+    EmptyLine,
+
+    Space(Spacing),
+}
+
+/// Optional spacing around punctuation, printed as a single space only if the
+/// corresponding printer option is enabled.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Spacing {
+    /// A space after a map or struct key's colon, `key: value`.
+    /// Controlled by `Printer.space_after_colon`.
+    AfterColon,
+    /// A space just inside a collection's brackets, `[ 1, 2 ]`.
+    /// Controlled by `Printer.space_inside_brackets`.
+    InsideBrackets,
+    /// A space that's always printed, e.g. where a document overrides the printer's options.
+    Always,
+}
+
+/// Which side of a document `Doc::Pad` puts its padding on.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PadSide {
+    /// Before the document, right-aligning it; see `pad_left`.
+    Left,
+    /// After the document, left-aligning it; see `pad_right`.
+    Right,
+}
+
+/// Text produced only once the printer reaches it; see `lazy`.
+/// Closures can't be compared, so lazy text is compared, hashed and ordered by identity.
+#[derive(Clone)]
+pub struct LazyText<'a>(
+    Arc<dyn Fn() -> String + Send + Sync + 'a>,
+    // Shared between clones, so the text is produced at most once
+    Arc<OnceLock<String>>,
+);
+
+impl LazyText<'_> {
+    /// Produce the text, or return it if it's been produced already.
+    pub fn text(&self) -> &str {
+        self.1.get_or_init(|| (self.0)())
+    }
+
+    fn addr(&self) -> usize {
+        Arc::as_ptr(&self.0) as *const () as usize
+    }
+}
+
+impl PartialEq for LazyText<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.addr() == other.addr()
+    }
+}
+
+impl Eq for LazyText<'_> {}
+
+impl Hash for LazyText<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.addr().hash(state);
+    }
+}
+
+impl PartialOrd for LazyText<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for LazyText<'_> {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.addr().cmp(&other.addr())
+    }
+}
+
+impl Doc<'_> {
+    /// Dump the document's tree as an s-expression, e.g.
+    /// `(group (concat "a" hardline (indent "b")))`.
+    /// Useful for debugging layout decisions, where the rendered output hides the structure.
+    pub fn to_sexpr(&self) -> String {
+        let mut out = String::new();
+        self.write_sexpr(&mut out);
+        out
+    }
+
+    fn write_sexpr(&self, out: &mut String) {
+        let mut list = |name: &str, docs: &mut dyn Iterator<Item = &Doc>| {
+            out.push('(');
+            out.push_str(name);
+            for doc in docs {
+                out.push(' ');
+                doc.write_sexpr(out);
+            }
+            out.push(')');
+        };
+
+        match self {
+            Doc::Null => out.push_str("null"),
+            Doc::String(s) => out.push_str(&format!("{:?}", s)),
+            Doc::Int(n) => out.push_str(&n.to_string()),
+            Doc::UInt(n) => out.push_str(&n.to_string()),
+            Doc::Lazy(_) => out.push_str("lazy"),
+            Doc::Concat(docs) => list("concat", &mut docs.iter()),
+            Doc::Group(d) => list("group", &mut std::iter::once(&**d)),
+            Doc::ConditionalGroup(alternatives) => {
+                list("conditional-group", &mut alternatives.iter())
+            }
+            Doc::Indent(d) => list("indent", &mut std::iter::once(&**d)),
+            Doc::Dedent(d) => list("dedent", &mut std::iter::once(&**d)),
+            Doc::IndentBy(step, d) => {
+                list(&format!("indent-by {}", step), &mut std::iter::once(&**d))
+            }
+            Doc::Join(sep, docs) => list("join", &mut std::iter::once(&**sep).chain(docs)),
+            Doc::SmartJoin(sep, docs) => {
+                list("smart-join", &mut std::iter::once(&**sep).chain(docs))
+            }
+            Doc::SmartJoinMax(sep, docs, max_per_line) => list(
+                &format!("smart-join-max {}", max_per_line),
+                &mut std::iter::once(&**sep).chain(docs),
+            ),
+            Doc::Unordered(d) => list("unordered", &mut std::iter::once(&**d)),
+            Doc::IfBreak(t, f) => list("if-break", &mut [&**t, &**f].into_iter()),
+            Doc::IfBreakRoot(f, t) => list("if-break-root", &mut [&**f, &**t].into_iter()),
+            Doc::Table(rows) => {
+                out.push_str("(table");
+                for row in rows {
+                    out.push_str(" (row");
+                    for cell in row {
+                        out.push(' ');
+                        cell.write_sexpr(out);
+                    }
+                    out.push(')');
+                }
+                out.push(')');
+            }
+            Doc::Prefix(prefix, d) => {
+                out.push_str(&format!("(prefix {:?} ", prefix));
+                d.write_sexpr(out);
+                out.push(')');
+            }
+            Doc::Pad(PadSide::Left, width, d) => {
+                list(&format!("pad-left {}", width), &mut std::iter::once(&**d))
+            }
+            Doc::Pad(PadSide::Right, width, d) => {
+                list(&format!("pad-right {}", width), &mut std::iter::once(&**d))
+            }
+            Doc::Hardline => out.push_str("hardline"),
+            Doc::Softline => out.push_str("softline"),
+            Doc::Mediumline => out.push_str("mediumline"),
+            Doc::Line => out.push_str("line"),
+            Doc::GroupLine(flat) => list("group-line", &mut std::iter::once(&**flat)),
+            Doc::EmptyLine => out.push_str("empty-line"),
+            Doc::Space(Spacing::AfterColon) => out.push_str("(space after-colon)"),
+            Doc::Space(Spacing::InsideBrackets) => out.push_str("(space inside-brackets)"),
+            Doc::Space(Spacing::Always) => out.push_str("(space always)"),
+        }
+    }
+}
+
+impl<'a> Doc<'a> {
+    /// Collapse redundant nesting, e.g. as left by derived or programmatically built
+    /// documents, without changing how the document renders: an indent of a dedent
+    /// cancels out, and single-document concatenations are unwrapped.
+    /// A dedent of an indent is kept, as the dedent stops at the margin, and so are nested
+    /// groups, as each group that breaks puts itself on lines of its own.
+    pub fn simplify(self) -> Doc<'a> {
+        let simplify_all = |docs: Vec<Doc<'a>>| docs.into_iter().map(Doc::simplify).collect();
+        let simplify_box = |doc: Box<Doc<'a>>| Box::new(doc.simplify());
+
+        match self {
+            Doc::Concat(docs) => {
+                let mut docs: Vec<_> = simplify_all(docs);
+                if docs.len() == 1 {
+                    docs.remove(0)
+                } else {
+                    Doc::Concat(docs)
+                }
+            }
+            Doc::Group(d) => group(d.simplify()),
+            Doc::ConditionalGroup(alternatives) => {
+                Doc::ConditionalGroup(simplify_all(alternatives))
+            }
+            Doc::Indent(d) => match d.simplify() {
+                Doc::Dedent(d) => *d,
+                doc => indent(doc),
+            },
+            Doc::Dedent(d) => dedent(d.simplify()),
+            Doc::IndentBy(step, d) => indent_by(step, d.simplify()),
+            Doc::Join(sep, docs) => Doc::Join(simplify_box(sep), simplify_all(docs)),
+            Doc::SmartJoin(sep, docs) => Doc::SmartJoin(simplify_box(sep), simplify_all(docs)),
+            Doc::SmartJoinMax(sep, docs, max_per_line) => {
+                Doc::SmartJoinMax(simplify_box(sep), simplify_all(docs), max_per_line)
+            }
+            Doc::Unordered(d) => Doc::Unordered(simplify_box(d)),
+            Doc::IfBreak(t, f) => Doc::IfBreak(simplify_box(t), simplify_box(f)),
+            Doc::IfBreakRoot(f, t) => Doc::IfBreakRoot(simplify_box(f), simplify_box(t)),
+            Doc::Table(rows) => Doc::Table(rows.into_iter().map(simplify_all).collect()),
+            Doc::Prefix(prefix, d) => Doc::Prefix(prefix, simplify_box(d)),
+            Doc::Pad(side, width, d) => Doc::Pad(side, width, simplify_box(d)),
+            Doc::GroupLine(d) => Doc::GroupLine(simplify_box(d)),
+            doc => doc,
+        }
+    }
 }
 
 impl<'a> std::ops::Add for Doc<'a> {
@@ -56,6 +300,58 @@ pub fn group<'a>(doc: impl Into<Doc<'a>>) -> Doc<'a> {
     Doc::Group(Box::new(doc.into()))
 }
 
+/// Alternative layouts of the same content, from flattest to most expanded, e.g. a call
+/// with all its arguments on one line, then with its last argument hugged, then with
+/// each argument on a line of its own.
+/// The first is printed if it fits flat. Otherwise, the first of the rest whose first
+/// line fits is, as with Prettier's `conditionalGroup`, so inherently multi-line layouts
+/// aren't ruled out; its first line runs up to its first line break, or its first group,
+/// which breaks onto lines of its own. If none fits, the last is printed.
+pub fn conditional_group<'a>(alternatives: Vec<impl Into<Doc<'a>>>) -> Doc<'a> {
+    Doc::ConditionalGroup(alternatives.into_iter().map(Into::into).collect())
+}
+
+/// Group a document only if `cond` holds, otherwise return it as is.
+pub fn group_if<'a>(cond: bool, doc: impl Into<Doc<'a>>) -> Doc<'a> {
+    if cond {
+        group(doc)
+    } else {
+        doc.into()
+    }
+}
+
+/// Force a document onto a single line, while leaving the rest of the document free
+/// to break: groups are unwrapped, smart joins become plain joins, `Line`s become spaces,
+/// and `Softline`s, `Mediumline`s and `EmptyLine`s are dropped. Hardlines are kept.
+/// The rewrite happens at construction, so the result may well overflow the page.
+pub fn flat<'a>(doc: impl Into<Doc<'a>>) -> Doc<'a> {
+    let flat_all = |docs: Vec<Doc<'a>>| docs.into_iter().map(flat).collect::<Vec<_>>();
+
+    match doc.into() {
+        Doc::Concat(docs) => Doc::Concat(flat_all(docs)),
+        Doc::Group(d) => flat(*d),
+        // The first alternative is the flattest
+        Doc::ConditionalGroup(mut alternatives) if !alternatives.is_empty() => {
+            flat(alternatives.swap_remove(0))
+        }
+        Doc::Indent(d) => indent(flat(*d)),
+        Doc::Dedent(d) => dedent(flat(*d)),
+        Doc::IndentBy(step, d) => indent_by(step, flat(*d)),
+        Doc::Join(sep, docs) | Doc::SmartJoin(sep, docs) | Doc::SmartJoinMax(sep, docs, _) => {
+            Doc::Join(Box::new(flat(*sep)), flat_all(docs))
+        }
+        Doc::Unordered(d) => Doc::Unordered(Box::new(flat(*d))),
+        Doc::IfBreak(_, other) | Doc::IfBreakRoot(other, _) => flat(*other),
+        Doc::Table(rows) => Doc::Table(rows.into_iter().map(flat_all).collect()),
+        Doc::Prefix(prefix, d) => Doc::Prefix(prefix, Box::new(flat(*d))),
+        Doc::Pad(side, width, d) => Doc::Pad(side, width, Box::new(flat(*d))),
+        Doc::Softline | Doc::Mediumline | Doc::EmptyLine => Doc::from(""),
+        Doc::Line => Doc::from(" "),
+        Doc::GroupLine(d) => flat(*d),
+        doc => doc,
+    }
+}
+
 /// Concatenate a vector of documents into a single document.
 pub fn concat<'a>(docs: Vec<impl Into<Doc<'a>>>) -> Doc<'a> {
     Doc::Concat(docs.into_iter().map(|d| d.into()).collect())
@@ -78,6 +374,25 @@ pub fn join<'a>(sep: impl Into<Doc<'a>>, docs: Vec<impl Into<Doc<'a>>>) -> Doc<'
     )
 }
 
+/// Interleave two vectors of documents element-wise, placing `mid` between each
+/// pair and joining the pairs on `sep`: `l0 mid r0 sep l1 mid r1 ...`.
+/// If the vectors differ in length, the output stops at the shorter of the two.
+pub fn zip_join<'a>(
+    left: Vec<impl Into<Doc<'a>>>,
+    right: Vec<impl Into<Doc<'a>>>,
+    mid: impl Into<Doc<'a>>,
+    sep: impl Into<Doc<'a>>,
+) -> Doc<'a> {
+    let mid = mid.into();
+    let pairs: Vec<_> = left
+        .into_iter()
+        .zip(right)
+        .map(|(l, r)| concat(vec![l.into(), mid.clone(), r.into()]))
+        .collect();
+
+    join(sep, pairs)
+}
+
 /// Join a vector of documents on a separator if the result fits the page,
 /// hence the name "smart join", otherwise join them on a line break.
 /// Implemented using the LaTeX algorithm described in
@@ -89,11 +404,33 @@ pub fn smart_join<'a>(sep: impl Into<Doc<'a>>, docs: Vec<impl Into<Doc<'a>>>) ->
     )
 }
 
+/// Join a vector of documents, placing `max_per_line` documents on each line,
+/// e.g. to print a matrix row by row. Lines are only broken sooner if they'd
+/// otherwise overflow the page.
+pub fn smart_join_max<'a>(
+    sep: impl Into<Doc<'a>>,
+    docs: Vec<impl Into<Doc<'a>>>,
+    max_per_line: usize,
+) -> Doc<'a> {
+    Doc::SmartJoinMax(
+        Box::new(sep.into()),
+        docs.into_iter().map(|d| d.into()).collect(),
+        max_per_line,
+    )
+}
+
 /// Indent a document by one level.
 pub fn indent<'a>(doc: impl Into<Doc<'a>>) -> Doc<'a> {
     Doc::Indent(Box::new(doc.into()))
 }
 
+/// Indent a document by `step` columns, rather than the printer's `indent`, e.g. to
+/// indent one block by 4 within a document indented by 2. Groups within it break
+/// back to where it started.
+pub fn indent_by<'a>(step: usize, doc: impl Into<Doc<'a>>) -> Doc<'a> {
+    Doc::IndentBy(step, Box::new(doc.into()))
+}
+
 /// Dedent a document by one level.
 pub fn dedent<'a>(doc: impl Into<Doc<'a>>) -> Doc<'a> {
     Doc::Dedent(Box::new(doc.into()))
@@ -109,19 +446,455 @@ pub fn softline<'a>() -> Doc<'a> {
     Doc::Softline
 }
 
+/// `n` absolute line breaks, i.e. `n - 1` blank lines.
+pub fn hardlines<'a>(n: usize) -> Doc<'a> {
+    Doc::Concat(vec![Doc::Hardline; n])
+}
+
+/// `n` spaces, e.g. for padding, borrowed rather than allocated for short runs.
+pub fn spaces<'a>(n: usize) -> Doc<'a> {
+    const SPACES: &str = "                                                                ";
+
+    match SPACES.get(..n) {
+        Some(spaces) => Doc::from(spaces),
+        None => Doc::from(" ".repeat(n)),
+    }
+}
+
+/// Right-align a document in a field `width` columns wide, by prepending spaces.
+/// The document is measured flat, as the printer printing it does, e.g. once escaped
+/// if it's ASCII-only, and left as is if it's already at least as wide.
+pub fn pad_left<'a>(doc: Doc<'a>, width: usize) -> Doc<'a> {
+    Doc::Pad(PadSide::Left, width, Box::new(doc))
+}
+
+/// Left-align a document in a field `width` columns wide, by appending spaces;
+/// see `pad_left`.
+pub fn pad_right<'a>(doc: Doc<'a>, width: usize) -> Doc<'a> {
+    Doc::Pad(PadSide::Right, width, Box::new(doc))
+}
+
+/// A document followed by a trailing comment starting at `column`, e.g. to align the
+/// comments of `key = value   # comment` lines. Documents reaching the column are
+/// separated from their comment by a single space.
+pub fn with_comment<'a>(doc: Doc<'a>, comment: Doc<'a>, column: usize) -> Doc<'a> {
+    // The space counts towards the padding, so is only added in full past the column
+    pad_right(doc + Doc::from(" "), column) + comment
+}
+
+/// Text computed by `f` only when the printer reaches it, e.g. for fields that are
+/// expensive to format, and may be left out by an `if_break`.
+/// Reaching it includes measuring it to decide whether an enclosing group breaks;
+/// either way, it's computed at most once. `f` must be `Send` and `Sync`, as documents are.
+pub fn lazy<'a>(f: impl Fn() -> String + Send + Sync + 'a) -> Doc<'a> {
+    Doc::Lazy(LazyText(Arc::new(f), Arc::default()))
+}
+
+/// Stack documents vertically, one per line.
+pub fn lines<'a>(docs: Vec<impl Into<Doc<'a>>>) -> Doc<'a> {
+    blank_lines(docs, 0)
+}
+
+/// Lines of text, e.g. of a log, one per line, where `Doc::from` would print a slice
+/// of them as a list.
+pub fn lines_from<'a>(lines: &[&'a str]) -> Doc<'a> {
+    join(Doc::Hardline, lines.to_vec())
+}
+
+/// A byte buffer: a quoted, escaped string if it's valid UTF-8, otherwise a hexdump,
+/// `<de ad be ef>`, of 16 bytes per line. `Doc::from` prints a `Vec<u8>` as a list
+/// of numbers instead.
+pub fn bytes<'a>(b: Vec<u8>) -> Doc<'a> {
+    match String::from_utf8(b) {
+        Ok(s) => Doc::from(format!("{:?}", s)),
+        Err(err) => {
+            let hex: Vec<_> = err
+                .as_bytes()
+                .iter()
+                .map(|b| Doc::from(format!("{:02x}", b)))
+                .collect();
+            bracket("<", smart_join_max(" ", hex, 16), ">")
+        }
+    }
+}
+
+/// A set of bit flags, `A | B | C`, naming each mask in `names` whose bits are all set,
+/// as `bitflags` prints them. Any bits left unnamed are printed in hex, `A | 0x40`,
+/// and no bits at all as `0x0`.
+pub fn flags<'a>(value: u64, names: &[(u64, &'a str)]) -> Doc<'a> {
+    let mut remaining = value;
+    let mut docs = Vec::new();
+    for &(mask, name) in names {
+        if mask != 0 && value & mask == mask {
+            remaining &= !mask;
+            docs.push(Doc::from(name));
+        }
+    }
+
+    if remaining != 0 || docs.is_empty() {
+        docs.push(Doc::from(format!("{:#x}", remaining)));
+    }
+    join(" | ", docs)
+}
+
+/// Fill in a template's `{}` placeholders with `args`, in order, interleaving its
+/// literal text and the arguments' documents, e.g. `template("{} = {}", &[key, value])`.
+/// As with `format!`, `{{` and `}}` print literal braces. Placeholders left without an
+/// argument are printed as is.
+pub fn template<'a>(fmt: &'a str, args: &[Doc<'a>]) -> Doc<'a> {
+    let mut docs = Vec::new();
+    let mut args = args.iter();
+    // The start of the literal text not yet added, and of the text left to scan
+    let (mut literal, mut i) = (0, 0);
+
+    while let Some(offset) = fmt[i..].find(['{', '}']) {
+        let at = i + offset;
+        let token = &fmt[at..];
+        let doc = if token.starts_with("{{") || token.starts_with("}}") {
+            Some(Doc::from(&fmt[at..at + 1]))
+        } else if token.starts_with("{}") {
+            args.next().cloned()
+        } else {
+            None
+        };
+
+        match doc {
+            Some(doc) => {
+                if literal < at {
+                    docs.push(Doc::from(&fmt[literal..at]));
+                }
+                docs.push(doc);
+                i = at + 2;
+                literal = i;
+            }
+            // A lone brace, or a placeholder without an argument, is left in the text
+            None => i = at + 1,
+        }
+    }
+    if literal < fmt.len() {
+        docs.push(Doc::from(&fmt[literal..]));
+    }
+    concat(docs)
+}
+
+/// Stack documents vertically, separated by `n` blank lines.
+/// The blank lines are left unindented, so as not to leave trailing whitespace.
+pub fn blank_lines<'a>(docs: Vec<impl Into<Doc<'a>>>, n: usize) -> Doc<'a> {
+    if docs.is_empty() {
+        return Doc::Null;
+    }
+    let mut sep = vec![Doc::Line; n];
+    sep.push(Doc::Hardline);
+
+    join(concat(sep), docs)
+}
+
+/// Pre-formatted, multi-line text, emitted line for line with its own spacing intact,
+/// but with each line re-indented to the current indentation level.
+/// A plain string containing newlines is instead emitted exactly as is.
+pub fn verbatim(s: &str) -> Doc<'_> {
+    join(Doc::Hardline, s.split('\n').collect())
+}
+
+/// A table of documents, printed one row per line, with each column padded
+/// to the width of its widest cell.
+pub fn table<'a>(rows: Vec<Vec<impl Into<Doc<'a>>>>) -> Doc<'a> {
+    Doc::Table(
+        rows.into_iter()
+            .map(|row| row.into_iter().map(|d| d.into()).collect())
+            .collect(),
+    )
+}
+
+/// Prefix every line of a document with the given string, e.g. `"> "` to quote it.
+/// The prefix follows the indentation the document starts at, and precedes any further
+/// indentation within it.
+pub fn prefix_lines<'a>(prefix: impl Into<Cow<'a, str>>, doc: impl Into<Doc<'a>>) -> Doc<'a> {
+    Doc::Prefix(prefix.into(), Box::new(doc.into()))
+}
+
+/// A line break if the enclosing group breaks, otherwise the `flat` document,
+/// e.g. `" "` to separate items on a single line.
+/// Outside of any group, this is always a line break.
+pub fn group_line<'a>(flat: impl Into<Doc<'a>>) -> Doc<'a> {
+    Doc::GroupLine(Box::new(flat.into()))
+}
+
+/// A bracketed, separated list: `[1, 2, 3]` if it fits the page, otherwise one item
+/// per line, indented, with a trailing separator after the last item.
+pub fn list<'a>(
+    open: impl Into<Doc<'a>>,
+    items: Vec<impl Into<Doc<'a>>>,
+    sep: impl Into<Doc<'a>>,
+    close: impl Into<Doc<'a>>,
+) -> Doc<'a> {
+    if items.is_empty() {
+        return concat(vec![open.into(), Doc::EmptyLine, close.into()]);
+    }
+    let sep = sep.into();
+
+    // The trailing separator is the group's last document, so it's followed by
+    // the group's closing line break exactly when the group breaks
+    (join(sep.clone() + group_line(" "), items) + if_break(sep, Doc::from("")))
+        .group()
+        .wrap(open, close)
+        .indent()
+}
+
 /// If the first document fits the page, print it, otherwise print the second document.
 pub fn if_break<'a>(doc: Doc<'a>, other: Doc<'a>) -> Doc<'a> {
     Doc::IfBreak(Box::new(doc), Box::new(other))
 }
 
+/// Print `flat` if the outermost group enclosing it fits the page, otherwise `broken`,
+/// whatever nested groups in between decide; e.g. to lay a document out differently
+/// only once it no longer fits on a line as a whole.
+/// Outside of any group, the page counts as broken.
+pub fn if_break_root<'a>(flat: impl Into<Doc<'a>>, broken: impl Into<Doc<'a>>) -> Doc<'a> {
+    Doc::IfBreakRoot(Box::new(flat.into()), Box::new(broken.into()))
+}
+
+/// Render an error followed by its chain of sources, each on its own line,
+/// prefixed by `caused by: ` and indented one level further than the last.
+pub fn error_chain<'a>(err: &dyn std::error::Error) -> Doc<'a> {
+    let doc = Doc::from(err.to_string());
+
+    match err.source() {
+        Some(source) => {
+            doc + indent(Doc::Hardline + Doc::from("caused by: ") + error_chain(source))
+        }
+        None => doc,
+    }
+}
+
+/// Whether a document always renders across multiple lines, i.e. contains an
+/// unconditional line break.
+fn is_multiline(doc: &Doc) -> bool {
+    match doc {
+        Doc::String(s) => s.contains('\n'),
+        Doc::Hardline | Doc::Line => true,
+        Doc::Concat(docs) => docs.iter().any(is_multiline),
+        Doc::Group(d)
+        | Doc::Indent(d)
+        | Doc::Dedent(d)
+        | Doc::IndentBy(_, d)
+        | Doc::Prefix(_, d)
+        | Doc::Pad(_, _, d)
+        | Doc::Unordered(d) => is_multiline(d),
+        Doc::Join(sep, docs) | Doc::SmartJoin(sep, docs) | Doc::SmartJoinMax(sep, docs, _) => {
+            docs.iter().any(is_multiline) || (docs.len() > 1 && is_multiline(sep))
+        }
+        Doc::IfBreak(t, f) | Doc::IfBreakRoot(f, t) => is_multiline(t) && is_multiline(f),
+        Doc::Table(rows) => rows.len() > 1 || rows.iter().flatten().any(is_multiline),
+        Doc::ConditionalGroup(alternatives) => {
+            !alternatives.is_empty() && alternatives.iter().all(is_multiline)
+        }
+        _ => false,
+    }
+}
+
+/// The value behind a shared mutex, e.g. an `Arc<Mutex<T>>`, or `<locked>` if it's locked
+/// elsewhere, rather than waiting on the lock, which could deadlock if it's held further
+/// up the stack.
+pub fn mutex<'a, T>(value: &Mutex<T>) -> Doc<'a>
+where
+    T: Into<Doc<'a>> + Clone,
+{
+    match value.try_lock() {
+        Ok(guard) => guard.clone().into(),
+        Err(TryLockError::Poisoned(err)) => err.into_inner().clone().into(),
+        Err(TryLockError::WouldBlock) => Doc::from("<locked>"),
+    }
+}
+
+/// A block-style key-value entry, `key: value`, as used by YAML-like output.
+/// Values that span multiple lines are placed on their own lines, indented below the key.
+pub fn block_entry<'a>(key: impl Into<Doc<'a>>, value: Doc<'a>) -> Doc<'a> {
+    if is_multiline(&value) {
+        concat(vec![
+            key.into(),
+            Doc::from(":"),
+            indent(Doc::Hardline + value),
+        ])
+    } else {
+        concat(vec![key.into(), Doc::from(": "), value])
+    }
+}
+
+/// An empty collection, e.g. `[]`, kept inline or expanded onto two lines according
+/// to `Printer.empty_collection`.
+pub fn empty<'a>(left: &'a str, right: &'a str) -> Doc<'a> {
+    concat(vec![Doc::from(left), Doc::EmptyLine, Doc::from(right)])
+}
+
+/// A block-style sequence, as used by YAML-like output: each item is printed
+/// on its own line, prefixed by `- `.
+pub fn block_seq<'a>(items: Vec<impl Into<Doc<'a>>>) -> Doc<'a> {
+    if items.is_empty() {
+        return empty("[", "]");
+    }
+
+    let items: Vec<_> = items
+        .into_iter()
+        .map(|item| Doc::from("- ") + indent(item))
+        .collect();
+
+    join(Doc::Hardline, items)
+}
+
+/// A sequence with each item prefixed by its index, as a map-like entry,
+/// `[0: a, 1: b, ...]`, e.g. for debugging which position holds what.
+pub fn enumerate_doc<'a, I>(items: I) -> Doc<'a>
+where
+    I: IntoIterator,
+    I::Item: Into<Doc<'a>>,
+{
+    let doc_vec: Vec<_> = items
+        .into_iter()
+        .enumerate()
+        .map(|(i, item)| key_value(i, item))
+        .collect();
+
+    if !doc_vec.is_empty() {
+        bracket("[", doc_vec.smart_join(", "), "]")
+    } else {
+        empty("[", "]")
+    }
+}
+
+/// A key-value pair, `key: value`, spaced according to `Printer.space_after_colon`.
+pub fn key_value<'a>(key: impl Into<Doc<'a>>, value: impl Into<Doc<'a>>) -> Doc<'a> {
+    concat(vec![
+        key.into(),
+        Doc::from(":"),
+        Doc::Space(Spacing::AfterColon),
+        value.into(),
+    ])
+}
+
+/// A map of key-value pairs, `{k: v, ...}`, printed like a `HashMap`, but in the
+/// order the pairs are given.
+pub fn map_from_pairs<'a, K, V>(pairs: Vec<(K, V)>) -> Doc<'a>
+where
+    K: Into<Doc<'a>>,
+    V: Into<Doc<'a>>,
+{
+    map_from(pairs)
+}
+
+/// A map of key-value pairs from any iterator, e.g. a `BTreeMap`, an `IndexMap`, or an
+/// array of pairs, printed like a `HashMap`, but in the iterator's order.
+pub fn map_from<'a, I, K, V>(pairs: I) -> Doc<'a>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<Doc<'a>>,
+    V: Into<Doc<'a>>,
+{
+    map_with(pairs, |entries| entries)
+}
+
+/// A set of items from any iterator, e.g. a `BTreeSet` or an `IndexSet`, printed
+/// like a `HashSet`, but in the iterator's order.
+pub fn set_from<'a, I>(items: I) -> Doc<'a>
+where
+    I: IntoIterator,
+    I::Item: Into<Doc<'a>>,
+{
+    set_with(items, |entries| entries)
+}
+
+/// A map of key-value pairs, with its joined entries passed through `entries`.
+fn map_with<'a, I, K, V>(pairs: I, entries: impl FnOnce(Doc<'a>) -> Doc<'a>) -> Doc<'a>
+where
+    I: IntoIterator<Item = (K, V)>,
+    K: Into<Doc<'a>>,
+    V: Into<Doc<'a>>,
+{
+    let doc_vec: Vec<_> = pairs
+        .into_iter()
+        .map(|(key, value)| key_value(key, value))
+        .collect();
+
+    if !doc_vec.is_empty() {
+        bracket(
+            "{",
+            entries(doc_vec.join(Doc::from(", ") + Doc::Hardline)),
+            "}",
+        )
+    } else {
+        empty("{", "}")
+    }
+}
+
+/// A set of items, with its joined entries passed through `entries`.
+fn set_with<'a, I>(items: I, entries: impl FnOnce(Doc<'a>) -> Doc<'a>) -> Doc<'a>
+where
+    I: IntoIterator,
+    I::Item: Into<Doc<'a>>,
+{
+    let doc_vec: Vec<_> = items.into_iter().map(|item| item.into()).collect();
+
+    if !doc_vec.is_empty() {
+        bracket("{", entries(doc_vec.smart_join(", ")), "}")
+    } else {
+        empty("{", "}")
+    }
+}
+
+/// Wrap the contents of a collection in brackets, `left` and `right`, grouping and
+/// indenting the contents, and padding them according to `Printer.space_inside_brackets`.
+/// The padding is omitted when the group breaks.
+pub fn bracket<'a>(left: &'a str, doc: impl Into<Doc<'a>>, right: &'a str) -> Doc<'a> {
+    delimit(left, doc, right, None)
+}
+
+/// As `bracket`, but with the padding inside the brackets overridden: `Some(true)`
+/// always pads them, `Some(false)` never does, and `None` defers to the printer.
+pub fn delimit<'a>(
+    left: &'a str,
+    doc: impl Into<Doc<'a>>,
+    right: &'a str,
+    spaced: Option<bool>,
+) -> Doc<'a> {
+    let space = match spaced {
+        Some(true) => Doc::Space(Spacing::Always),
+        Some(false) => Doc::from(""),
+        None => Doc::Space(Spacing::InsideBrackets),
+    };
+    (space.clone() + doc.into())
+        .group()
+        .wrap(left, space + Doc::from(right))
+        .indent()
+}
+
+/// Wrap a document in braces, `{...}`; see `delimit` for `spaced`.
+pub fn braces<'a>(doc: impl Into<Doc<'a>>, spaced: Option<bool>) -> Doc<'a> {
+    delimit("{", doc, "}", spaced)
+}
+
+/// Wrap a document in square brackets, `[...]`; see `delimit` for `spaced`.
+pub fn brackets<'a>(doc: impl Into<Doc<'a>>, spaced: Option<bool>) -> Doc<'a> {
+    delimit("[", doc, "]", spaced)
+}
+
+/// Wrap a document in parentheses, `(...)`; see `delimit` for `spaced`.
+pub fn parens<'a>(doc: impl Into<Doc<'a>>, spaced: Option<bool>) -> Doc<'a> {
+    delimit("(", doc, ")", spaced)
+}
+
 pub trait Group {
     fn group(self) -> Self;
+    fn group_if(self, cond: bool) -> Self;
 }
 
 impl Group for Doc<'_> {
     fn group(self) -> Self {
         group(self)
     }
+
+    fn group_if(self, cond: bool) -> Self {
+        group_if(cond, self)
+    }
 }
 
 pub trait Indent {
@@ -148,7 +921,10 @@ pub trait Join<'a> {
     fn join(self, sep: impl Into<Doc<'a>>) -> Doc<'a>;
 }
 
-impl<'a> Join<'a> for Vec<Doc<'a>> {
+impl<'a, T> Join<'a> for Vec<T>
+where
+    T: Into<Doc<'a>>,
+{
     fn join(self, sep: impl Into<Doc<'a>>) -> Doc<'a> {
         join(sep, self)
     }
@@ -158,14 +934,37 @@ pub trait SmartJoin<'a> {
     fn smart_join(self, sep: impl Into<Doc<'a>>) -> Doc<'a>;
 }
 
-impl<'a> SmartJoin<'a> for Vec<Doc<'a>> {
+impl<'a, T> SmartJoin<'a> for Vec<T>
+where
+    T: Into<Doc<'a>>,
+{
     fn smart_join(self, sep: impl Into<Doc<'a>>) -> Doc<'a> {
         smart_join(sep, self)
     }
 }
 
-pub trait Wrap<'a> {
+pub trait Wrap<'a>: Sized {
     fn wrap(self, left: impl Into<Doc<'a>>, right: impl Into<Doc<'a>>) -> Doc<'a>;
+
+    /// Wrap in braces, `{...}`. Unlike `braces`, the contents are neither grouped nor indented.
+    fn braces(self) -> Doc<'a> {
+        self.wrap("{", "}")
+    }
+
+    /// Wrap in square brackets, `[...]`; see `Wrap::braces`.
+    fn brackets(self) -> Doc<'a> {
+        self.wrap("[", "]")
+    }
+
+    /// Wrap in parentheses, `(...)`; see `Wrap::braces`.
+    fn parens(self) -> Doc<'a> {
+        self.wrap("(", ")")
+    }
+
+    /// Wrap in double quotes, `"..."`. The contents aren't escaped; see `escape` for that.
+    fn quoted(self) -> Doc<'a> {
+        self.wrap("\"", "\"")
+    }
 }
 
 impl<'a> Wrap<'a> for Doc<'a> {
@@ -186,6 +985,17 @@ impl<'a> From<String> for Doc<'a> {
     }
 }
 
+/// Formatted text, e.g. `Doc::from(format_args!("{} {}", a, b))`, is formatted straight
+/// into the document's string, and not at all if it's only a literal.
+impl<'a> From<Arguments<'_>> for Doc<'a> {
+    fn from(args: Arguments<'_>) -> Doc<'a> {
+        match args.as_str() {
+            Some(s) => Doc::from(s),
+            None => Doc::from(args.to_string()),
+        }
+    }
+}
+
 impl<'a> From<bool> for Doc<'a> {
     fn from(b: bool) -> Doc<'a> {
         Doc::String(b.to_string().into())
@@ -203,7 +1013,88 @@ macro_rules! impl_from_number_to_doc {
         )*
     };
 }
-impl_from_number_to_doc!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+impl_from_number_to_doc!(f32, f64);
+
+macro_rules! impl_from_integer_to_doc {
+    ($variant:ident as $wide:ty: $($t:ty),*) => {
+        $(
+            impl<'a> From<$t> for Doc<'a> {
+                fn from(value: $t) -> Self {
+                    Doc::$variant(value as $wide)
+                }
+            }
+        )*
+    };
+}
+impl_from_integer_to_doc!(Int as i128: i8, i16, i32, i64, i128, isize);
+impl_from_integer_to_doc!(UInt as u128: u8, u16, u32, u64, u128, usize);
+
+macro_rules! impl_from_non_zero_to_doc {
+    ($($t:ty),*) => {
+        $(
+            impl<'a> From<$t> for Doc<'a> {
+                fn from(value: $t) -> Self {
+                    value.get().into()
+                }
+            }
+        )*
+    };
+}
+impl_from_non_zero_to_doc!(
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroI128,
+    NonZeroIsize,
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroU128,
+    NonZeroUsize
+);
+
+macro_rules! impl_from_number_wrapper_to_doc {
+    ($($t:ident),*) => {
+        $(
+            impl<'a, T> From<$t<T>> for Doc<'a>
+            where
+                T: Into<Doc<'a>>,
+            {
+                fn from(value: $t<T>) -> Self {
+                    value.0.into()
+                }
+            }
+        )*
+    };
+}
+impl_from_number_wrapper_to_doc!(Wrapping, Saturating);
+
+// Fieldless std enums print as their variant's name, as `Debug` would
+macro_rules! impl_from_std_enum_to_doc {
+    ($($t:ident { $($variant:ident),* })*) => {
+        $(
+            impl<'a> From<$t> for Doc<'a> {
+                fn from(value: $t) -> Self {
+                    // The fallback is for `#[non_exhaustive]` enums
+                    #[allow(unreachable_patterns)]
+                    match value {
+                        $($t::$variant => Doc::from(stringify!($variant)),)*
+                        other => Doc::from(format!("{:?}", other)),
+                    }
+                }
+            }
+        )*
+    };
+}
+impl_from_std_enum_to_doc! {
+    CmpOrdering { Less, Equal, Greater }
+    AtomicOrdering { Relaxed, Release, Acquire, AcqRel, SeqCst }
+    FpCategory { Nan, Infinite, Zero, Subnormal, Normal }
+    Alignment { Left, Right, Center }
+    Shutdown { Read, Write, Both }
+}
 
 impl<'a, T> From<Option<T>> for Doc<'a>
 where
@@ -217,6 +1108,58 @@ where
     }
 }
 
+/// A tuple variant, `Name(value)`, as `Debug` prints it.
+fn tuple_variant<'a>(name: &'a str, value: impl Into<Doc<'a>>) -> Doc<'a> {
+    Doc::from(name) + parens(value, Some(false))
+}
+
+impl<'a, B, C> From<ControlFlow<B, C>> for Doc<'a>
+where
+    B: Into<Doc<'a>>,
+    C: Into<Doc<'a>>,
+{
+    fn from(flow: ControlFlow<B, C>) -> Doc<'a> {
+        match flow {
+            ControlFlow::Continue(value) => tuple_variant("Continue", value),
+            ControlFlow::Break(value) => tuple_variant("Break", value),
+        }
+    }
+}
+
+impl<'a, T> From<Bound<T>> for Doc<'a>
+where
+    T: Into<Doc<'a>>,
+{
+    fn from(bound: Bound<T>) -> Doc<'a> {
+        match bound {
+            Bound::Included(value) => tuple_variant("Included", value),
+            Bound::Excluded(value) => tuple_variant("Excluded", value),
+            Bound::Unbounded => Doc::from("Unbounded"),
+        }
+    }
+}
+
+/// Printed as written, e.g. `0..10`.
+impl<'a, T> From<Range<T>> for Doc<'a>
+where
+    T: Into<Doc<'a>>,
+{
+    fn from(range: Range<T>) -> Doc<'a> {
+        concat(vec![range.start.into(), Doc::from(".."), range.end.into()])
+    }
+}
+
+/// Printed as written, e.g. `0..=9`.
+impl<'a, T> From<RangeInclusive<T>> for Doc<'a>
+where
+    T: Into<Doc<'a>>,
+{
+    fn from(range: RangeInclusive<T>) -> Doc<'a> {
+        let (start, end) = range.into_inner();
+        concat(vec![start.into(), Doc::from("..="), end.into()])
+    }
+}
+
 impl<'a, T> From<&[T]> for Doc<'a>
 where
     T: Into<Doc<'a>> + Clone,
@@ -230,6 +1173,23 @@ where
     }
 }
 
+impl<'a, T> From<Box<[T]>> for Doc<'a>
+where
+    T: Into<Doc<'a>>,
+{
+    fn from(slice: Box<[T]>) -> Doc<'a> {
+        slice.into_vec().into()
+    }
+}
+
+impl<T: ?Sized> From<PhantomData<T>> for Doc<'_> {
+    fn from(_: PhantomData<T>) -> Self {
+        Doc::Null
+    }
+}
+
+/// The unit value renders as `()`; unit structs and enum variants deriving `Pretty`
+/// instead render as their name.
 impl From<()> for Doc<'_> {
     fn from(_: ()) -> Self {
         Doc::from("()")
@@ -245,6 +1205,15 @@ where
     }
 }
 
+impl<'a, T> From<&mut T> for Doc<'a>
+where
+    T: Into<Doc<'a>> + Clone,
+{
+    fn from(value: &mut T) -> Self {
+        value.clone().into()
+    }
+}
+
 impl<'a, T> From<Box<T>> for Doc<'a>
 where
     T: Into<Doc<'a>>,
@@ -254,15 +1223,112 @@ where
     }
 }
 
-impl<'a> From<Cow<'a, str>> for Doc<'a> {
-    fn from(cow: Cow<'a, str>) -> Self {
+impl<'a, T> From<Rc<T>> for Doc<'a>
+where
+    T: Into<Doc<'a>> + Clone,
+{
+    // Only cloned if the value is shared
+    fn from(value: Rc<T>) -> Self {
+        Rc::unwrap_or_clone(value).into()
+    }
+}
+
+impl<'a, T> From<Arc<T>> for Doc<'a>
+where
+    T: Into<Doc<'a>> + Clone,
+{
+    // Only cloned if the value is shared
+    fn from(value: Arc<T>) -> Self {
+        Arc::unwrap_or_clone(value).into()
+    }
+}
+
+impl<'a, T> From<RefCell<T>> for Doc<'a>
+where
+    T: Into<Doc<'a>>,
+{
+    fn from(value: RefCell<T>) -> Self {
+        value.into_inner().into()
+    }
+}
+
+impl<'a, T> From<Cell<T>> for Doc<'a>
+where
+    T: Into<Doc<'a>> + Copy,
+{
+    fn from(value: Cell<T>) -> Self {
+        value.get().into()
+    }
+}
+
+/// A mutex that's owned can't be locked elsewhere, so it's unwrapped directly; see `mutex`
+/// for one that's shared. A poisoned mutex still renders its value, as the panic that
+/// poisoned it is likely what's being debugged.
+impl<'a, T> From<Mutex<T>> for Doc<'a>
+where
+    T: Into<Doc<'a>>,
+{
+    fn from(value: Mutex<T>) -> Self {
+        value
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+            .into()
+    }
+}
+
+/// A `Cow` converts as whichever of its borrowed or owned forms it holds, so e.g. a
+/// borrowed `Cow<str>` is printed without being copied.
+impl<'a, 'b, B> From<Cow<'b, B>> for Doc<'a>
+where
+    B: ToOwned + ?Sized,
+    &'b B: Into<Doc<'a>>,
+    B::Owned: Into<Doc<'a>>,
+{
+    fn from(cow: Cow<'b, B>) -> Self {
         match cow {
-            Cow::Borrowed(s) => s.into(),
-            Cow::Owned(s) => s.into(),
+            Cow::Borrowed(borrowed) => borrowed.into(),
+            Cow::Owned(owned) => owned.into(),
+        }
+    }
+}
+
+/// OS strings are converted lossily: invalid unicode is replaced with `U+FFFD`, `�`.
+/// Valid unicode is borrowed rather than copied.
+impl<'a> From<&'a OsStr> for Doc<'a> {
+    fn from(s: &'a OsStr) -> Self {
+        s.to_string_lossy().into()
+    }
+}
+
+impl<'a> From<OsString> for Doc<'a> {
+    fn from(s: OsString) -> Self {
+        match s.into_string() {
+            Ok(s) => s.into(),
+            Err(s) => s.to_string_lossy().into_owned().into(),
         }
     }
 }
 
+impl<'a> From<&dyn std::error::Error> for Doc<'a> {
+    fn from(err: &dyn std::error::Error) -> Self {
+        error_chain(err)
+    }
+}
+
+#[cfg(feature = "anyhow")]
+impl<'a> From<&anyhow::Error> for Doc<'a> {
+    fn from(err: &anyhow::Error) -> Self {
+        error_chain(err.as_ref())
+    }
+}
+
+#[cfg(feature = "eyre")]
+impl<'a> From<&eyre::Report> for Doc<'a> {
+    fn from(report: &eyre::Report) -> Self {
+        error_chain(report.as_ref())
+    }
+}
+
 impl<'a> From<Regex> for Doc<'a> {
     fn from(regex: Regex) -> Self {
         regex.as_str().to_owned().into()
@@ -278,15 +1344,22 @@ macro_rules! impl_from_tuple_to_doc {
         {
             fn from(tuple: ($($t),*)) -> Self {
                 let ($($t),*) = tuple;
-                vec![$($t.into()),*]
-                    .smart_join(", ")
-                    .group()
-                    .wrap("(", ")")
+                delimit("(", vec![$($t.into()),*].smart_join(", "), ")", Some(false))
             }
         }
     };
 }
 
+/// A single-element tuple keeps its trailing comma, `(x,)`, as in Rust's syntax.
+impl<'a, T> From<(T,)> for Doc<'a>
+where
+    T: Into<Doc<'a>>,
+{
+    fn from((value,): (T,)) -> Self {
+        (value.into() + Doc::from(",")).wrap("(", ")")
+    }
+}
+
 impl_from_tuple_to_doc!(T1, T2);
 impl_from_tuple_to_doc!(T1, T2, T3);
 impl_from_tuple_to_doc!(T1, T2, T3, T4);
@@ -298,44 +1371,104 @@ impl_from_tuple_to_doc!(T1, T2, T3, T4, T5, T6, T7, T8, T9);
 impl_from_tuple_to_doc!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10);
 impl_from_tuple_to_doc!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11);
 impl_from_tuple_to_doc!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12);
+impl_from_tuple_to_doc!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13);
+impl_from_tuple_to_doc!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14);
+impl_from_tuple_to_doc!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15);
+impl_from_tuple_to_doc!(T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16);
+
+// Larger tuples are opt-in, as each impl adds to the crate's compile time
+#[cfg(feature = "large-tuples")]
+mod large_tuples {
+    use super::*;
+
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24, T25
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24, T25, T26
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24, T25, T26, T27
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24, T25, T26, T27, T28
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24, T25, T26, T27, T28, T29
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24, T25, T26, T27, T28, T29, T30
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24, T25, T26, T27, T28, T29, T30, T31
+    );
+    impl_from_tuple_to_doc!(
+        T1, T2, T3, T4, T5, T6, T7, T8, T9, T10, T11, T12, T13, T14, T15, T16, T17, T18, T19, T20,
+        T21, T22, T23, T24, T25, T26, T27, T28, T29, T30, T31, T32
+    );
+}
 
 impl<'a, T> From<Vec<T>> for Doc<'a>
 where
     T: Into<Doc<'a>>,
 {
     fn from(vec: Vec<T>) -> Doc<'a> {
-        let doc_vec: Vec<_> = vec.into_iter().map(|item| item.into()).collect();
+        // Collected in place, so a `Vec<Doc>` keeps its allocation; the join is built
+        // directly, rather than with `smart_join`, which would convert each item again
+        let doc_vec: Vec<Doc<'a>> = vec.into_iter().map(|item| item.into()).collect();
 
         if !doc_vec.is_empty() {
-            let doc = doc_vec.smart_join(", ").group().wrap("[", "]").indent();
-            doc
+            bracket("[", Doc::SmartJoin(Box::new(", ".into()), doc_vec), "]")
         } else {
-            Doc::from("[]")
+            empty("[", "]")
         }
     }
 }
 
+/// Keys and values are converted as themselves, so borrowed text, e.g. `&str` keys,
+/// stays borrowed, rather than being copied.
 impl<'a, K, V, R> From<HashMap<K, V, R>> for Doc<'a>
 where
     K: Into<Doc<'a>>,
     V: Into<Doc<'a>>,
 {
     fn from(map: HashMap<K, V, R>) -> Doc<'a> {
-        let doc_vec: Vec<_> = map
-            .into_iter()
-            .map(|(key, value)| key.into() + Doc::from(": ") + value.into())
-            .collect();
-
-        if !doc_vec.is_empty() {
-            let doc = doc_vec
-                .join(Doc::from(", ") + Doc::Hardline)
-                .group()
-                .wrap("{", "}")
-                .indent();
-            doc
-        } else {
-            Doc::from("{}")
-        }
+        map_with(map, |entries| Doc::Unordered(Box::new(entries)))
     }
 }
 
@@ -344,13 +1477,103 @@ where
     T: Into<Doc<'a>>,
 {
     fn from(set: HashSet<T>) -> Self {
-        let doc_vec: Vec<_> = set.into_iter().map(|item| item.into()).collect();
+        set_with(set, |entries| Doc::Unordered(Box::new(entries)))
+    }
+}
 
-        if !doc_vec.is_empty() {
-            let doc = doc_vec.smart_join(", ").group().wrap("{", "}").indent();
-            doc
-        } else {
-            Doc::from("{}")
+/// Format seconds and nanoseconds since the Unix epoch as an RFC 3339 UTC timestamp,
+/// e.g. `2001-02-03T04:05:06.789Z`, with the fraction omitted when it's zero.
+fn rfc3339_utc(secs: i64, nanos: u32) -> String {
+    let (days, secs_of_day) = (secs.div_euclid(86_400), secs.rem_euclid(86_400));
+
+    // Howard Hinnant's days-to-civil-date algorithm, with eras of 400 years
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    let fraction = match nanos {
+        0 => String::new(),
+        n if n % 1_000_000 == 0 => format!(".{:03}", n / 1_000_000),
+        n if n % 1_000 == 0 => format!(".{:06}", n / 1_000),
+        n => format!(".{:09}", n),
+    };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day / 60 % 60,
+        secs_of_day % 60,
+        fraction
+    )
+}
+
+impl<'a> From<SystemTime> for Doc<'a> {
+    fn from(time: SystemTime) -> Self {
+        let (secs, nanos) = match time.duration_since(UNIX_EPOCH) {
+            Ok(since) => (since.as_secs() as i64, since.subsec_nanos()),
+            Err(err) => {
+                let before = err.duration();
+                match before.subsec_nanos() {
+                    0 => (-(before.as_secs() as i64), 0),
+                    nanos => (-(before.as_secs() as i64) - 1, 1_000_000_000 - nanos),
+                }
+            }
+        };
+        rfc3339_utc(secs, nanos).into()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<'a, Tz> From<chrono::DateTime<Tz>> for Doc<'a>
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    fn from(datetime: chrono::DateTime<Tz>) -> Self {
+        datetime.to_rfc3339().into()
+    }
+}
+
+#[cfg(feature = "time")]
+impl<'a> From<time::OffsetDateTime> for Doc<'a> {
+    fn from(datetime: time::OffsetDateTime) -> Self {
+        // RFC 3339 formatting only fails for years outside 0..=9999
+        datetime
+            .format(&time::format_description::well_known::Rfc3339)
+            .unwrap_or_else(|_| datetime.to_string())
+            .into()
+    }
+}
+
+#[cfg(feature = "serde_json")]
+impl<'a> From<serde_json::Value> for Doc<'a> {
+    fn from(value: serde_json::Value) -> Self {
+        use serde_json::Value;
+
+        match value {
+            Value::Null => Doc::from("null"),
+            Value::Bool(b) => b.into(),
+            Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+                (Some(n), _) => n.into(),
+                (_, Some(n)) => n.into(),
+                _ => n.to_string().into(),
+            },
+            // Re-serialize to get JSON's quoting and escaping
+            Value::String(_) => value.to_string().into(),
+            Value::Array(values) => values.into(),
+            Value::Object(map) => map_from_pairs(
+                map.into_iter()
+                    .map(|(key, value)| (Value::String(key), value))
+                    .collect(),
+            ),
         }
     }
 }
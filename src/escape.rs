@@ -0,0 +1,69 @@
+use std::borrow::Cow;
+
+use crate::doc::Doc;
+
+/// The target language a string is escaped for by `escape`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EscapeKind {
+    /// A double-quoted JSON string literal.
+    Json,
+    /// A POSIX shell word, single-quoted unless it consists only of safe characters.
+    Shell,
+    /// A CSV field, double-quoted only if it contains a delimiter, quote, or line break.
+    Csv,
+    /// A single-quoted SQL string literal.
+    Sql,
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn escape_shell(s: &str) -> Cow<'_, str> {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c);
+
+    if !s.is_empty() && s.chars().all(is_safe) {
+        Cow::Borrowed(s)
+    } else {
+        // Single quotes can't be escaped within single quotes, so close the quote,
+        // emit an escaped quote, and reopen it.
+        Cow::Owned(format!("'{}'", s.replace('\'', "'\\''")))
+    }
+}
+
+fn escape_csv(s: &str) -> Cow<'_, str> {
+    if s.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", s.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(s)
+    }
+}
+
+fn escape_sql(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "''"))
+}
+
+/// Escape and quote a string for the given target language.
+/// The string is borrowed, rather than copied, where no escaping is needed.
+pub fn escape(s: &str, kind: EscapeKind) -> Doc<'_> {
+    match kind {
+        EscapeKind::Json => escape_json(s).into(),
+        EscapeKind::Shell => escape_shell(s).into(),
+        EscapeKind::Csv => escape_csv(s).into(),
+        EscapeKind::Sql => escape_sql(s).into(),
+    }
+}
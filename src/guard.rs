@@ -0,0 +1,121 @@
+use crate::doc::Doc;
+use crate::print::{pprint, Printer, PRINTER};
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+
+/// Tracks the shared pointers being converted into a document, to detect cycles.
+#[derive(Debug)]
+pub struct CycleGuard {
+    visiting: HashSet<usize>,
+    ellipsis: Cow<'static, str>,
+}
+
+impl Default for CycleGuard {
+    fn default() -> Self {
+        Self::with_ellipsis(PRINTER.ellipsis)
+    }
+}
+
+impl CycleGuard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A guard printing cycles' back-references as `ellipsis`, rather than the default `…`.
+    pub fn with_ellipsis(ellipsis: impl Into<Cow<'static, str>>) -> Self {
+        CycleGuard {
+            visiting: HashSet::new(),
+            ellipsis: ellipsis.into(),
+        }
+    }
+
+    /// Convert the value behind `ptr` with `f`, unless it's already being converted,
+    /// i.e. it's reachable from itself, in which case it's printed as the ellipsis.
+    /// Values that are merely shared, rather than cyclic, are printed in full each time.
+    pub fn visit<T: ?Sized>(
+        &mut self,
+        ptr: *const T,
+        f: impl FnOnce(&mut Self) -> Doc<'static>,
+    ) -> Doc<'static> {
+        let addr = ptr as *const () as usize;
+        if !self.visiting.insert(addr) {
+            return Doc::from(self.ellipsis.clone());
+        }
+        let doc = f(self);
+        self.visiting.remove(&addr);
+        doc
+    }
+}
+
+/// Conversion into a document for possibly cyclic structures, e.g. those built
+/// of `Rc<RefCell<T>>`, where the `From` conversions would recurse forever.
+/// Implementations convert shared pointers through the `CycleGuard`.
+pub trait PrettyGuarded {
+    fn pretty_guarded(&self, guard: &mut CycleGuard) -> Doc<'static>;
+}
+
+/// Pretty print a possibly cyclic value, printing each cycle's back-reference as
+/// the printer's ellipsis.
+pub fn pprint_guarded<T: PrettyGuarded + ?Sized>(value: &T, printer: &Printer) -> String {
+    let doc = value.pretty_guarded(&mut CycleGuard::with_ellipsis(printer.ellipsis.clone()));
+    pprint(&doc, printer)
+}
+
+impl<T: PrettyGuarded + ?Sized> PrettyGuarded for Rc<T> {
+    fn pretty_guarded(&self, guard: &mut CycleGuard) -> Doc<'static> {
+        guard.visit(Rc::as_ptr(self), |guard| (**self).pretty_guarded(guard))
+    }
+}
+
+impl<T: PrettyGuarded + ?Sized> PrettyGuarded for RefCell<T> {
+    fn pretty_guarded(&self, guard: &mut CycleGuard) -> Doc<'static> {
+        self.borrow().pretty_guarded(guard)
+    }
+}
+
+impl<T: PrettyGuarded + ?Sized> PrettyGuarded for Box<T> {
+    fn pretty_guarded(&self, guard: &mut CycleGuard) -> Doc<'static> {
+        (**self).pretty_guarded(guard)
+    }
+}
+
+impl<T: PrettyGuarded> PrettyGuarded for Option<T> {
+    fn pretty_guarded(&self, guard: &mut CycleGuard) -> Doc<'static> {
+        match self {
+            Some(value) => value.pretty_guarded(guard),
+            None => Doc::from("None"),
+        }
+    }
+}
+
+impl<T: PrettyGuarded> PrettyGuarded for Vec<T> {
+    fn pretty_guarded(&self, guard: &mut CycleGuard) -> Doc<'static> {
+        self.iter()
+            .map(|item| item.pretty_guarded(guard))
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl PrettyGuarded for str {
+    fn pretty_guarded(&self, _: &mut CycleGuard) -> Doc<'static> {
+        Doc::from(self.to_owned())
+    }
+}
+
+macro_rules! impl_pretty_guarded_via_from {
+    ($($t:ty),*) => {
+        $(
+            impl PrettyGuarded for $t {
+                fn pretty_guarded(&self, _: &mut CycleGuard) -> Doc<'static> {
+                    Doc::from(self.clone())
+                }
+            }
+        )*
+    };
+}
+impl_pretty_guarded_via_from!(
+    String, bool, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64
+);
@@ -7,5 +7,20 @@ pub use print::*;
 pub mod utils;
 pub use utils::*;
 
+pub mod compiled;
+pub use compiled::*;
+
+pub mod escape;
+pub use escape::*;
+
+pub mod guard;
+pub use guard::*;
+
+pub mod builder;
+pub use builder::*;
+
+#[cfg(feature = "insta")]
+pub mod snapshot;
+
 extern crate pprint_derive;
 pub use pprint_derive::*;
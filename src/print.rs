@@ -1,40 +1,301 @@
-use crate::doc::Doc;
-use crate::utils::text_justify;
+use crate::doc::{Doc, PadSide, Spacing};
+use crate::utils::{digit_count, escape_non_ascii, greedy_line_breaks, line_breaks, text_width};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Flat widths of a document's nodes, keyed by the node's address.
+pub(crate) type Widths = HashMap<*const (), usize>;
+
+fn node_key(doc: &Doc) -> *const () {
+    doc as *const Doc as *const ()
+}
+
+/// The flat width of a document, looked up in `widths` if it's been measured already.
+fn width_of(doc: &Doc, printer: &Printer, widths: Option<&Widths>) -> usize {
+    widths
+        .and_then(|widths| widths.get(&node_key(doc)).copied())
+        .unwrap_or_else(|| display_width(doc, printer))
+}
+
+/// As `width_of`, but measuring no further than needed to tell that the document is wider
+/// than `cap`: the exact width if it fits within `cap`, else some width greater than it.
+/// Deciding whether a long group fits then takes only its first line's worth of measuring.
+fn capped_width(doc: &Doc, printer: &Printer, widths: Option<&Widths>, cap: usize) -> usize {
+    if let Some(&width) = widths.and_then(|widths| widths.get(&node_key(doc))) {
+        return width;
+    }
+
+    let capped_sum = |docs: &mut dyn Iterator<Item = &Doc>| {
+        let mut width = 0usize;
+        for d in docs {
+            width =
+                width.saturating_add(capped_width(d, printer, widths, cap.saturating_sub(width)));
+            if width > cap {
+                break;
+            }
+        }
+        width
+    };
+    let capped_join = |sep: &Doc, docs: &[Doc]| {
+        let mut joined = docs.iter().enumerate().flat_map(|(i, d)| {
+            let sep = (i > 0).then_some(sep);
+            sep.into_iter().chain(std::iter::once(d))
+        });
+        capped_sum(&mut joined)
+    };
+
+    match doc {
+        Doc::Concat(docs) => capped_sum(&mut docs.iter()),
+        Doc::Join(sep, docs) => capped_join(sep, docs),
+        // Wrapping only ever widens a smart join, so it's measured in full only if it fits
+        Doc::SmartJoin(sep, docs) | Doc::SmartJoinMax(sep, docs, _)
+            if capped_join(sep, docs) > cap =>
+        {
+            cap.saturating_add(1)
+        }
+        Doc::Group(d) | Doc::Unordered(d) | Doc::GroupLine(d) => {
+            capped_width(d, printer, widths, cap)
+        }
+        Doc::ConditionalGroup(alternatives) => alternatives
+            .first()
+            .map_or(0, |d| capped_width(d, printer, widths, cap)),
+        Doc::Prefix(prefix, d) => {
+            text_width(prefix).saturating_add(capped_width(d, printer, widths, cap))
+        }
+        Doc::Indent(d) | Doc::IndentBy(_, d) if printer.exact_widths => {
+            capped_width(d, printer, widths, cap)
+        }
+        Doc::Indent(d) => capped_width(d, printer, widths, cap).saturating_add(printer.indent),
+        Doc::IndentBy(step, d) => capped_width(d, printer, widths, cap).saturating_add(*step),
+        Doc::IfBreak(t, f) | Doc::IfBreakRoot(f, t) => {
+            capped_width(t, printer, widths, cap).max(capped_width(f, printer, widths, cap))
+        }
+        // Dedents, whose width shrinks, and leaves are measured in full
+        _ => width_of(doc, printer, widths),
+    }
+}
+
+/// Measure the flat width of every node of a document, recording each in `widths`.
+pub(crate) fn measure_nodes(doc: &Doc, printer: &Printer, widths: &mut Widths) -> usize {
+    let width = text_length_with(doc, printer, &mut |d| measure_nodes(d, printer, widths));
+    widths.insert(node_key(doc), width);
+    width
+}
+
+/// Sum lengths, saturating rather than overflowing, since documents containing line breaks
+/// measure as wide as the page, which may be as wide as `usize::MAX`.
+fn saturating_sum(lengths: impl Iterator<Item = usize>) -> usize {
+    lengths.fold(0, usize::saturating_add)
+}
 
 pub fn count_join_length<'a>(sep: &'a Doc<'a>, docs: &'a Vec<Doc<'a>>, printer: &Printer) -> usize {
+    join_length(sep, docs, &mut |d| display_width(d, printer))
+}
+
+fn join_length(sep: &Doc, docs: &[Doc], measure: &mut dyn FnMut(&Doc) -> usize) -> usize {
     if docs.is_empty() {
         return 0;
     }
-    let doc_length: usize = docs.iter().map(|d| count_text_length(d, printer)).sum();
-    let separator_length = count_text_length(sep, printer);
+    let doc_length =
+        integers_width(docs).unwrap_or_else(|| saturating_sum(docs.iter().map(&mut *measure)));
+    let separator_length = measure(sep);
 
-    doc_length + separator_length * (docs.len() - 1)
+    doc_length.saturating_add(separator_length.saturating_mul(docs.len() - 1))
 }
 
+/// The combined width of documents that are all integers, e.g. those of a `Vec<i64>`,
+/// summed in one tight pass rather than measuring each in turn; `None` if any isn't.
+fn integers_width(docs: &[Doc]) -> Option<usize> {
+    docs.iter().try_fold(0usize, |sum, doc| {
+        let width = match doc {
+            Doc::Int(n) => int_width(*n),
+            Doc::UInt(n) => digit_count(*n),
+            _ => return None,
+        };
+        Some(sum.saturating_add(width))
+    })
+}
+
+fn int_width(n: i128) -> usize {
+    usize::from(n < 0) + digit_count(n.unsigned_abs())
+}
+
+/// The display width of a document printed flat, i.e. the columns it takes up on a single
+/// line, as the printer measures it when deciding where to break; see `text_width`.
+/// Line breaks count as the page width, so that documents containing them never fit.
+pub fn display_width(doc: &Doc, printer: &Printer) -> usize {
+    text_length_with(doc, printer, &mut |d| display_width(d, printer))
+}
+
+#[deprecated(note = "use `display_width`")]
 pub fn count_text_length(doc: &Doc, printer: &Printer) -> usize {
+    display_width(doc, printer)
+}
+
+/// The flat width of a document, measuring each of its children with `measure`.
+fn text_length_with(doc: &Doc, printer: &Printer, measure: &mut dyn FnMut(&Doc) -> usize) -> usize {
     match doc {
-        Doc::String(s) => s.len(),
-        Doc::Concat(docs) => docs.iter().map(|d| count_text_length(d, printer)).sum(),
-        Doc::Group(d) => count_text_length(d, printer),
-        Doc::Indent(d) => count_text_length(d, printer).saturating_add(printer.indent),
-        Doc::Dedent(d) => count_text_length(d, printer).saturating_sub(printer.indent),
-        Doc::Join(sep, docs) => count_join_length(sep, docs, printer),
-        Doc::IfBreak(t, f) => count_text_length(t, printer).max(count_text_length(f, printer)),
-        Doc::SmartJoin(sep, docs) => {
-            let length = count_join_length(sep, docs, printer);
-            if length * docs.len() >= printer.max_width {
-                length + printer.max_width
+        Doc::String(s) => str_width(s, printer),
+        Doc::Null => printer
+            .null_display
+            .as_deref()
+            .map_or(0, |s| str_width(s, printer)),
+        Doc::Int(n) => int_width(*n),
+        Doc::UInt(n) => digit_count(*n),
+        // Computed to be measured, and kept for when it's printed
+        Doc::Lazy(text) => str_width(text.text(), printer),
+        Doc::Concat(docs) => saturating_sum(docs.iter().map(measure)),
+        Doc::Group(d) | Doc::Unordered(d) => measure(d),
+        // Measured as its flattest alternative
+        Doc::ConditionalGroup(alternatives) => alternatives.first().map_or(0, measure),
+        Doc::Prefix(prefix, d) => text_width(prefix).saturating_add(measure(d)),
+        Doc::Pad(_, width, d) => measure(d).max(*width),
+        // Exactly, indentation only takes up space after a line break
+        Doc::Indent(d) | Doc::Dedent(d) | Doc::IndentBy(_, d) if printer.exact_widths => measure(d),
+        Doc::Indent(d) => measure(d).saturating_add(printer.indent),
+        Doc::IndentBy(step, d) => measure(d).saturating_add(*step),
+        Doc::Dedent(d) => measure(d).saturating_sub(printer.indent),
+        Doc::Join(sep, docs) => join_length(sep, docs, measure),
+        Doc::IfBreak(t, f) | Doc::IfBreakRoot(f, t) => measure(t).max(measure(f)),
+        // A smart join is measured flat, unless it'll wrap onto several lines regardless
+        // of the page width, in which case it's measured as though it contained a hardline,
+        // so that its enclosing group breaks too.
+        Doc::SmartJoin(sep, docs) | Doc::SmartJoinMax(sep, docs, _) => {
+            let length = join_length(sep, docs, measure);
+
+            let wraps = match doc {
+                Doc::SmartJoinMax(_, _, max_per_line) => docs.len() > (*max_per_line).max(1),
+                // Run the justification itself, rather than estimating whether it'll wrap
+                _ if printer.exact_widths => {
+                    let doc_lengths: Vec<_> = docs.iter().map(&mut *measure).collect();
+                    !justified_line_breaks(measure(sep), &doc_lengths, printer).is_empty()
+                }
+                // A greedy fill wraps as soon as the documents overflow the justified width
+                _ if printer.wrap_algorithm == WrapAlgo::Greedy => {
+                    docs.len() > 1 && length > justify_width(printer)
+                }
+                // Justification only wraps the last document onto its own line once
+                // everything before it fills the justified width
+                _ => match docs.last() {
+                    Some(last) if docs.len() > 1 => {
+                        length.saturating_sub(measure(sep).saturating_add(measure(last)))
+                            >= justify_width(printer)
+                    }
+                    _ => false,
+                },
+            };
+
+            if wraps {
+                length.saturating_add(printer.max_width)
             } else {
                 length
             }
         }
+        Doc::Table(rows) => {
+            let widths = column_widths_with(rows, measure);
+            if rows.len() > 1 {
+                printer.max_width
+            } else {
+                saturating_sum(widths.iter().copied())
+                    .saturating_add(widths.len().saturating_sub(1))
+            }
+        }
+        // Exactly, a softline is as wide as its flat form, nothing, as it only breaks
+        // once the line overflows anyway
+        Doc::Softline if printer.exact_widths => 0,
         Doc::Hardline | Doc::Mediumline | Doc::Line => printer.max_width,
         Doc::Softline => printer.max_width / 2,
+        Doc::GroupLine(flat) => measure(flat),
+        Doc::EmptyLine if printer.empty_collection == CollapseStyle::Expanded => printer.max_width,
+        Doc::Space(spacing) if printer.has_spacing(*spacing) => 1,
         _ => 0,
     }
 }
 
+/// The width of a document's first line, i.e. up to its first line break, and whether
+/// it ends there, rather than running on into whatever follows it.
+/// A group ends the line straight away, as it breaks onto lines of its own if it's
+/// too wide; softer breaks are measured flat, as they only break once the line overflows.
+fn first_line_width(doc: &Doc, printer: &Printer, widths: Option<&Widths>) -> (usize, bool) {
+    let concat = |docs: &mut dyn Iterator<Item = &Doc>| {
+        let mut width = 0usize;
+        for d in docs {
+            let (w, ended) = first_line_width(d, printer, widths);
+            width = width.saturating_add(w);
+            if ended {
+                return (width, true);
+            }
+        }
+        (width, false)
+    };
+
+    match doc {
+        Doc::Hardline | Doc::Line | Doc::GroupLine(_) => (0, true),
+        Doc::Group(_) => (0, true),
+        Doc::EmptyLine if printer.empty_collection == CollapseStyle::Expanded => (0, true),
+        Doc::String(s) => match s.split_once('\n') {
+            Some((line, _)) => (str_width(line, printer), true),
+            None => (str_width(s, printer), false),
+        },
+        Doc::Concat(docs) => concat(&mut docs.iter()),
+        Doc::Join(sep, docs) | Doc::SmartJoin(sep, docs) | Doc::SmartJoinMax(sep, docs, _) => {
+            let mut joined = docs.iter().enumerate().flat_map(|(i, d)| {
+                let sep = (i > 0).then_some(&**sep);
+                sep.into_iter().chain(std::iter::once(d))
+            });
+            concat(&mut joined)
+        }
+        Doc::Indent(d)
+        | Doc::Dedent(d)
+        | Doc::IndentBy(_, d)
+        | Doc::Unordered(d)
+        | Doc::IfBreak(d, _)
+        | Doc::IfBreakRoot(_, d) => first_line_width(d, printer, widths),
+        Doc::Prefix(prefix, d) => {
+            let (width, ended) = first_line_width(d, printer, widths);
+            (text_width(prefix).saturating_add(width), ended)
+        }
+        Doc::Pad(side, width, d) => {
+            let padding = width.saturating_sub(width_of(d, printer, widths));
+            match first_line_width(d, printer, widths) {
+                // Padding after the document follows its last line, not its first
+                (first, true) if *side == PadSide::Right => (first, true),
+                (first, ended) => (first.saturating_add(padding), ended),
+            }
+        }
+        Doc::ConditionalGroup(alternatives) => alternatives
+            .first()
+            .map_or((0, false), |d| first_line_width(d, printer, widths)),
+        Doc::Table(rows) => {
+            let widths = column_widths_with(rows, &mut |d| width_of(d, printer, widths));
+            let width = saturating_sum(widths.iter().copied())
+                .saturating_add(widths.len().saturating_sub(1));
+            (width, rows.len() > 1)
+        }
+        Doc::Softline | Doc::Mediumline => (0, false),
+        _ => (width_of(doc, printer, widths), false),
+    }
+}
+
+/// The width of each column of a table, i.e. the width of its widest cell.
+pub fn column_widths(rows: &[Vec<Doc>], printer: &Printer) -> Vec<usize> {
+    column_widths_with(rows, &mut |d| display_width(d, printer))
+}
+
+fn column_widths_with(rows: &[Vec<Doc>], measure: &mut dyn FnMut(&Doc) -> usize) -> Vec<usize> {
+    rows.iter().fold(Vec::new(), |mut widths, row| {
+        for (i, cell) in row.iter().enumerate() {
+            let width = measure(cell);
+            match widths.get_mut(i) {
+                Some(w) => *w = width.max(*w),
+                None => widths.push(width),
+            }
+        }
+        widths
+    })
+}
+
 pub fn join_impl<'a>(sep: &'a Doc<'a>, docs: &'a [Doc], _: &Printer) -> Vec<&'a Doc<'a>> {
     docs.iter()
         .enumerate()
@@ -47,24 +308,105 @@ pub fn join_impl<'a>(sep: &'a Doc<'a>, docs: &'a [Doc], _: &Printer) -> Vec<&'a
         })
 }
 
+/// The width smart joins justify their lines to: a quarter of the page width.
+fn justify_width(printer: &Printer) -> usize {
+    (printer.max_width / 4).max(2)
+}
+
+/// The indices of the documents that start each of a smart join's justified lines,
+/// as chosen by the printer's wrap algorithm.
+fn justified_line_breaks(
+    sep_length: usize,
+    doc_lengths: &[usize],
+    printer: &Printer,
+) -> Vec<usize> {
+    // Documents that fit on a single justified line, with room to spare, as small vectors'
+    // usually do, are never broken by either algorithm, so needn't be run through one.
+    // Exactly full lines are left to the algorithms, which may still break off empty
+    // documents at their ends.
+    let seps = sep_length.saturating_mul(doc_lengths.len().saturating_sub(1));
+    if saturating_sum(doc_lengths.iter().copied()).saturating_add(seps) < justify_width(printer) {
+        return Vec::new();
+    }
+
+    let line_breaks = match printer.wrap_algorithm {
+        WrapAlgo::MinRaggedness => line_breaks,
+        WrapAlgo::Greedy => greedy_line_breaks,
+    };
+    line_breaks(sep_length, doc_lengths, justify_width(printer))
+}
+
+/// Join documents, breaking lines where the text justification algorithm sees fit.
+/// Lines are justified to a quarter of the page width, and are additionally broken
+/// wherever they'd otherwise overflow the page, given that the join starts at `column`
+/// and each broken line starts at `indent_delta`.
+/// If `max_per_line` is given, lines are instead filled with up to that many documents,
+/// still breaking early wherever they'd otherwise overflow the page.
 pub fn smart_join_impl<'a>(
     sep: &'a Doc<'a>,
     docs: &'a [Doc],
     printer: &Printer,
+    column: usize,
+    indent_delta: usize,
+    max_per_line: Option<usize>,
 ) -> Vec<&'a Doc<'a>> {
-    let max_width = (printer.max_width / 4).max(2);
+    smart_join_in(sep, docs, printer, None, column, indent_delta, max_per_line)
+}
 
-    let sep_length = count_text_length(sep, printer);
-    let doc_lengths: Vec<_> = docs.iter().map(|d| count_text_length(d, printer)).collect();
+fn smart_join_in<'a>(
+    sep: &'a Doc<'a>,
+    docs: &'a [Doc],
+    printer: &Printer,
+    widths: Option<&Widths>,
+    column: usize,
+    indent_delta: usize,
+    max_per_line: Option<usize>,
+) -> Vec<&'a Doc<'a>> {
+    let sep_length = width_of(sep, printer, widths);
+    let doc_lengths: Vec<_> = docs.iter().map(|d| width_of(d, printer, widths)).collect();
 
-    let breaks = text_justify(sep_length, &doc_lengths, max_width);
+    let mut justified_breaks = vec![false; docs.len()];
+    if max_per_line.is_none() {
+        for i in justified_line_breaks(sep_length, &doc_lengths, printer) {
+            justified_breaks[i] = true;
+        }
+    }
+
+    // Walk the justified lines, tracking the real column, and break early wherever
+    // a document (and the separator that may trail it) would overflow the page.
+    let mut breaks = vec![false; docs.len()];
+    let mut column = column;
+    let mut items_on_line = 0;
+
+    for (i, &length) in doc_lengths.iter().enumerate() {
+        if i > 0 {
+            column = column.saturating_add(sep_length);
+
+            let trailing = if i + 1 < docs.len() { sep_length } else { 0 };
+            let overflows =
+                saturating_sum([column, length, trailing].into_iter()) > printer.max_width;
+            let is_full = max_per_line.is_some_and(|max| items_on_line >= max);
+
+            if justified_breaks[i] || overflows || is_full {
+                debug_assert!(
+                    items_on_line == 1 || column <= printer.max_width,
+                    "smart join line overflows the page"
+                );
+                breaks[i] = true;
+                column = indent_delta;
+                items_on_line = 0;
+            }
+        }
+        column = column.saturating_add(length);
+        items_on_line += 1;
+    }
 
     docs.iter()
         .enumerate()
         .fold(Vec::new(), |mut acc, (i, doc)| {
             if i > 0 {
                 acc.push(sep);
-                if breaks.contains(&i) {
+                if breaks[i] {
                     acc.push(&Doc::Hardline);
                 }
             }
@@ -73,65 +415,595 @@ pub fn smart_join_impl<'a>(
         })
 }
 
+/// Whether a document is made up solely of literal text, i.e. contains no line breaks,
+/// groups, or joins, and so renders identically regardless of the page width.
+pub fn is_literal(doc: &Doc) -> bool {
+    match doc {
+        Doc::Null | Doc::String(_) | Doc::Int(_) | Doc::UInt(_) => true,
+        Doc::Concat(docs) => docs.iter().all(is_literal),
+        _ => false,
+    }
+}
+
+/// The display width of a string as the printer writes it, i.e. once escaped,
+/// if the printer is ASCII-only.
+fn str_width(s: &str, printer: &Printer) -> usize {
+    if printer.ascii_only {
+        text_width(&escape_non_ascii(s))
+    } else {
+        text_width(s)
+    }
+}
+
+/// Write a string to the output, escaping it if the printer is ASCII-only,
+/// and returning its display width.
+fn write_str(s: &str, output: &mut String, printer: &Printer) -> usize {
+    let s = if printer.ascii_only {
+        escape_non_ascii(s)
+    } else {
+        s.into()
+    };
+    output.push_str(&s);
+    text_width(&s)
+}
+
+/// Write an integer to the output, without an intermediate allocation,
+/// returning its width.
+fn write_int(n: i128, output: &mut String) -> usize {
+    if n < 0 {
+        output.push('-');
+        1 + write_uint(n.unsigned_abs(), output)
+    } else {
+        write_uint(n.unsigned_abs(), output)
+    }
+}
+
+fn write_uint(n: u128, output: &mut String) -> usize {
+    use std::fmt::Write;
+
+    let start = output.len();
+    // Writing to a String never fails. 128-bit formatting is several times slower than
+    // 64-bit, and most integers fit in 64 bits, so they're formatted as such.
+    let _ = match u64::try_from(n) {
+        Ok(n) => write!(output, "{}", n),
+        Err(_) => write!(output, "{}", n),
+    };
+    output.len() - start
+}
+
+/// Write the printer's `null_display`, if any, returning its display width.
+fn write_null(output: &mut String, printer: &Printer) -> usize {
+    printer
+        .null_display
+        .as_deref()
+        .map_or(0, |s| write_str(s, output, printer))
+}
+
+/// Write a literal document directly to the output, returning its display width.
+fn write_literal(doc: &Doc, output: &mut String, printer: &Printer) -> usize {
+    match doc {
+        Doc::String(s) => write_str(s, output, printer),
+        Doc::Null => write_null(output, printer),
+        Doc::Int(n) => write_int(*n, output),
+        Doc::UInt(n) => write_uint(*n, output),
+        Doc::Concat(docs) => docs.iter().map(|d| write_literal(d, output, printer)).sum(),
+        _ => 0,
+    }
+}
+
+/// The length of the current line once the output from `start` on, `width` columns wide,
+/// is written to it. Text with embedded line breaks restarts the line, so only what
+/// follows the last break counts; the breaks are recorded in `line_breaks`.
+fn line_len_after(
+    current_line_len: usize,
+    width: usize,
+    output: &str,
+    start: usize,
+    line_breaks: &mut Vec<usize>,
+) -> usize {
+    let written = &output[start..];
+    match written.rfind('\n') {
+        Some(i) => {
+            line_breaks.extend(written.match_indices('\n').map(|(j, _)| start + j));
+            text_width(&written[i + 1..])
+        }
+        None => current_line_len.saturating_add(width),
+    }
+}
+
+/// Whether nothing but indentation, and the line's prefix, if any, has been printed
+/// on the current line.
+fn at_line_start(output: &str, prefix: Option<&LinePrefix>) -> bool {
+    let line = output.rsplit('\n').next().unwrap_or_default();
+    let line = prefix
+        .and_then(|prefix| line.strip_prefix(prefix.text.as_str()))
+        .unwrap_or(line);
+    line.trim_start().is_empty()
+}
+
+/// The prefix printed at the start of every line within a `Doc::Prefix`.
+/// `text` holds the full start of the line, i.e. any indentation and enclosing prefixes,
+/// up to the indentation level `indent`; indentation beyond that follows the prefix.
+struct LinePrefix {
+    indent: usize,
+    text: String,
+}
+
+/// Start a new line, indented to `indent_delta`, returning the length of the new line.
+/// Indentation is sliced from `indentation`, a run of `space`s that's grown as needed.
+fn push_newline(
+    output: &mut String,
+    line_breaks: &mut Vec<usize>,
+    indentation: &mut String,
+    space: &str,
+    indent_delta: usize,
+    prefix: Option<&LinePrefix>,
+) -> usize {
+    line_breaks.push(output.len());
+    output.push('\n');
+
+    let prefix_len = match prefix {
+        Some(prefix) => {
+            output.push_str(&prefix.text);
+            text_width(&prefix.text)
+        }
+        None => 0,
+    };
+    let indent_delta = indent_delta.saturating_sub(prefix.map_or(0, |p| p.indent));
+
+    let line_len = indent_delta * space.len();
+    while indentation.len() < line_len {
+        indentation.push_str(space);
+    }
+    output.push_str(&indentation[..line_len]);
+
+    prefix_len.saturating_add(indent_delta.saturating_mul(text_width(space)))
+}
+
+/// Remove the last line break, if nothing's been printed since, so that the break about
+/// to be printed replaces it, rather than leaving a blank line.
+fn undo_adjacent_break(
+    output: &mut String,
+    line_breaks: &mut Vec<usize>,
+    last_break: Option<(usize, usize)>,
+) {
+    if let Some((start, end)) = last_break {
+        if end == output.len() {
+            output.truncate(start);
+            line_breaks.pop();
+        }
+    }
+}
+
+/// Split off as much of `text` as fits in `room` columns, as the line to print before
+/// breaking, and the rest, or `None` if it all fits, or can't be broken.
+/// Words that are too long to fit on a line by themselves are left whole, unless
+/// breaking by character, in which case at least one character is always split off.
+fn split_long_text(text: &str, room: usize, style: BreakStyle) -> Option<(&str, &str)> {
+    if text_width(text) <= room {
+        return None;
+    }
+    let fits = |end: &usize| text_width(&text[..*end]) <= room;
+
+    match style {
+        BreakStyle::Word => {
+            let at = text
+                .match_indices(' ')
+                .map(|(i, _)| i)
+                .take_while(fits)
+                .last()
+                .or_else(|| text.find(' '))?;
+            Some((&text[..at], &text[at + 1..]))
+        }
+        BreakStyle::Char => {
+            let at = text
+                .char_indices()
+                .map(|(i, c)| i + c.len_utf8())
+                .take_while(fits)
+                .last()
+                .or_else(|| Some(text.chars().next()?.len_utf8()))?;
+            Some(text.split_at(at))
+        }
+    }
+}
+
 /// Core pretty printing function.
 /// Takes a document and a printer configuration and returns a String.
 /// Uses a stack to avoid recursion, keeping track of the current line length,
 /// and indent level.
 pub fn pprint<'a>(doc: &'a Doc<'a>, printer: &Printer) -> String {
+    pprint_in(doc, printer, None)
+}
+
+/// As `pprint`, but borrowing the document's text, rather than allocating, when the
+/// document is a single string that the printer would print unchanged.
+pub fn pprint_cow<'a>(doc: &'a Doc<'a>, printer: &Printer) -> Cow<'a, str> {
+    match doc {
+        Doc::String(s)
+            if (!printer.ascii_only || s.is_ascii())
+                && !printer.line_numbers
+                && printer.max_output_bytes.is_none()
+                && !(printer.break_long_text && str_width(s, printer) > printer.max_width)
+                && printer.prologue.is_none()
+                && printer.epilogue.is_none() =>
+        {
+            Cow::Borrowed(s)
+        }
+        _ => Cow::Owned(pprint(doc, printer)),
+    }
+}
+
+/// Sort the entries of the document's unordered collections, e.g. a `HashMap`'s,
+/// by key, i.e. each entry's first document, then by the entry as a whole.
+fn sort_unordered(doc: &mut Doc, printer: &Printer) {
+    let printer = Printer {
+        sort_keys: false,
+        ..printer.clone()
+    };
+    sort_unordered_with(doc, &printer);
+}
+
+fn sort_unordered_with(doc: &mut Doc, printer: &Printer) {
+    match doc {
+        Doc::Unordered(d) => {
+            sort_unordered_with(d, printer);
+            if let Doc::Join(_, docs) | Doc::SmartJoin(_, docs) | Doc::SmartJoinMax(_, docs, _) =
+                &mut **d
+            {
+                docs.sort_by_cached_key(|entry| {
+                    let key = match entry {
+                        Doc::Concat(parts) => parts.first().map(|key| pprint(key, printer)),
+                        _ => None,
+                    };
+                    (key, pprint(entry, printer))
+                });
+            }
+        }
+        Doc::Concat(docs) => docs
+            .iter_mut()
+            .for_each(|d| sort_unordered_with(d, printer)),
+        Doc::Group(d)
+        | Doc::Indent(d)
+        | Doc::Dedent(d)
+        | Doc::IndentBy(_, d)
+        | Doc::Prefix(_, d)
+        | Doc::Pad(_, _, d)
+        | Doc::GroupLine(d) => sort_unordered_with(d, printer),
+        Doc::Join(sep, docs) | Doc::SmartJoin(sep, docs) | Doc::SmartJoinMax(sep, docs, _) => {
+            sort_unordered_with(sep, printer);
+            docs.iter_mut()
+                .for_each(|d| sort_unordered_with(d, printer));
+        }
+        Doc::IfBreak(t, f) | Doc::IfBreakRoot(f, t) => {
+            sort_unordered_with(t, printer);
+            sort_unordered_with(f, printer);
+        }
+        Doc::ConditionalGroup(alternatives) => alternatives
+            .iter_mut()
+            .for_each(|d| sort_unordered_with(d, printer)),
+        Doc::Table(rows) => rows
+            .iter_mut()
+            .flatten()
+            .for_each(|d| sort_unordered_with(d, printer)),
+        _ => {}
+    }
+}
+
+/// Pretty print a document, looking up the flat widths of its nodes in `widths`,
+/// and measuring any nodes missing from it as they're printed.
+pub(crate) fn pprint_in<'a>(
+    doc: &'a Doc<'a>,
+    printer: &Printer,
+    widths: Option<&Widths>,
+) -> String {
+    render_finished(doc, printer, widths).output
+}
+
+/// Render a document, then dress up the output as the printer's options ask, e.g. with
+/// line numbers, or a prologue; every way of printing a document is built on this.
+fn render_finished(doc: &Doc, printer: &Printer, widths: Option<&Widths>) -> Rendered {
+    let mut rendered = render(doc, printer, widths);
+    if printer.line_numbers {
+        rendered = rendered.numbered();
+    }
+    if printer.prologue.is_some() || printer.epilogue.is_some() {
+        rendered.enclose(
+            printer.prologue.as_deref().unwrap_or(""),
+            printer.epilogue.as_deref().unwrap_or(""),
+        );
+    }
+    // Once, on the whole output, rather than on each table cell rendered along the way,
+    // and last, so that the gutter and enclosure count towards the limit too
+    if let Some(max_bytes) = printer.max_output_bytes {
+        rendered.truncate(max_bytes, &printer.ellipsis);
+    }
+    rendered
+}
+
+/// A column of a right-padded document's padding, printed after the document.
+static PAD_SPACE: Doc<'static> = Doc::String(Cow::Borrowed(" "));
+
+/// The printer's output, along with where it broke its lines.
+struct Rendered {
+    output: String,
+    // The byte offset of each line break in the output
+    line_breaks: Vec<usize>,
+    groups_broken: usize,
+}
+
+impl Rendered {
+    /// The output with each line prefixed by its number, right-aligned in a gutter.
+    fn numbered(&self) -> Rendered {
+        use std::fmt::Write;
+
+        let lines = self.line_breaks.len() + 1;
+        let width = digit_count(lines as u128);
+        let mut output = String::with_capacity(self.output.len() + (width + 3) * lines);
+        let mut line_breaks = Vec::with_capacity(self.line_breaks.len());
+        for (i, line) in self.lines().enumerate() {
+            if i > 0 {
+                line_breaks.push(output.len());
+                output.push('\n');
+            }
+            if line.is_empty() {
+                let _ = write!(output, "{:>width$} |", i + 1);
+            } else {
+                let _ = write!(output, "{:>width$} | {}", i + 1, line);
+            }
+        }
+        Rendered {
+            output,
+            line_breaks,
+            groups_broken: self.groups_broken,
+        }
+    }
+
+    /// Write `prologue` before the output, and `epilogue` after it, breaking lines where
+    /// either contains a newline.
+    fn enclose(&mut self, prologue: &str, epilogue: &str) {
+        let newlines = |text: &str, offset: usize| {
+            text.match_indices('\n')
+                .map(move |(i, _)| offset + i)
+                .collect::<Vec<_>>()
+        };
+
+        let mut line_breaks = newlines(prologue, 0);
+        line_breaks.extend(self.line_breaks.iter().map(|i| prologue.len() + i));
+        line_breaks.extend(newlines(epilogue, prologue.len() + self.output.len()));
+
+        self.output = [prologue, &self.output, epilogue].concat();
+        self.line_breaks = line_breaks;
+    }
+
+    /// Cut the output short at `max_bytes`, if it's longer, marking where it was cut.
+    fn truncate(&mut self, max_bytes: usize, ellipsis: &str) {
+        if self.output.len() <= max_bytes {
+            return;
+        }
+        let end = (0..=max_bytes)
+            .rev()
+            .find(|&i| self.output.is_char_boundary(i))
+            .unwrap_or(0);
+        self.output.truncate(end);
+        self.line_breaks.retain(|&i| i < end);
+        self.output.push_str(ellipsis);
+        self.output.push_str(" (truncated)");
+    }
+
+    /// The output's lines, split at the recorded line breaks.
+    fn lines(&self) -> impl Iterator<Item = &str> {
+        let starts = std::iter::once(0).chain(self.line_breaks.iter().map(|i| i + 1));
+        let ends = self.line_breaks.iter().copied().chain([self.output.len()]);
+        starts
+            .zip(ends)
+            .map(|(start, end)| &self.output[start..end])
+    }
+}
+
+/// The printer's driver.
+fn render<'a>(doc: &'a Doc<'a>, printer: &Printer, widths: Option<&Widths>) -> Rendered {
+    // Sorted on a copy, whose nodes' widths, keyed by address, are measured afresh
+    if printer.sort_keys {
+        let mut sorted = doc.clone();
+        sort_unordered(&mut sorted, printer);
+        let printer = Printer {
+            sort_keys: false,
+            ..printer.clone()
+        };
+        return render(&sorted, &printer, None);
+    }
+
+    #[derive(Clone)]
     struct PrintItem<'a> {
         doc: &'a Doc<'a>,
         indent_delta: usize,
+        prefix: Option<Rc<LinePrefix>>,
+        // Whether the innermost enclosing group was broken
+        broken: bool,
+        // Whether the outermost enclosing group was, if there is one
+        root_broken: Option<bool>,
+        // The step of the innermost enclosing indent, which a broken group dedents by
+        indent_step: usize,
+    }
+
+    impl<'a> PrintItem<'a> {
+        // A child item, printed in the same context as this one
+        fn with(&self, doc: &'a Doc<'a>) -> Self {
+            PrintItem {
+                doc,
+                ..self.clone()
+            }
+        }
+
+        fn with_indent(&self, doc: &'a Doc<'a>, indent_delta: usize) -> Self {
+            PrintItem {
+                doc,
+                indent_delta,
+                ..self.clone()
+            }
+        }
     }
 
     let mut output = String::new();
+    let mut line_breaks = Vec::new();
     let mut current_line_len = 0;
 
-    let push_hardline = |stack: &mut Vec<_>, indent_delta: usize| {
-        stack.push(PrintItem {
-            doc: &Doc::Hardline,
-            indent_delta,
-        });
-    };
-
     let mut stack = vec![PrintItem {
         doc,
         indent_delta: 0,
+        prefix: None,
+        broken: true,
+        root_broken: None,
+        indent_step: printer.indent,
     }];
 
-    let mut hardlines = HashMap::new();
+    let mut indentation = String::new();
+    let mut groups_broken = 0;
+    // Where the last `Line` or `Hardline` started, and where its indentation ended
+    let mut last_break: Option<(usize, usize)> = None;
 
-    let space = if printer.use_tabs { "\t" } else { " " };
+    let space = printer.indent_str();
+    let space = space.as_str();
+
+    while let Some(item) = stack.pop() {
+        // Numbered output is rendered in full, as its gutter is as wide as its line count
+        if !printer.line_numbers
+            && printer
+                .max_output_bytes
+                .is_some_and(|max_bytes| output.len() > max_bytes)
+        {
+            break;
+        }
+
+        let PrintItem {
+            doc,
+            indent_delta,
+            ref prefix,
+            broken,
+            root_broken,
+            indent_step,
+        } = item;
 
-    while let Some(PrintItem { doc, indent_delta }) = stack.pop() {
         match &doc {
+            // Text that overflows the line is broken across several, continuing each
+            // at the current indentation.
+            Doc::String(s) if printer.break_long_text => {
+                let mut rest: &str = s;
+                while let Some((line, next)) = split_long_text(
+                    rest,
+                    printer.max_width.saturating_sub(current_line_len),
+                    printer.long_text_break,
+                ) {
+                    let start = output.len();
+                    let width = write_str(line, &mut output, printer);
+                    line_len_after(current_line_len, width, &output, start, &mut line_breaks);
+                    current_line_len = push_newline(
+                        &mut output,
+                        &mut line_breaks,
+                        &mut indentation,
+                        space,
+                        indent_delta,
+                        prefix.as_deref(),
+                    );
+                    rest = next;
+                }
+                let start = output.len();
+                let width = write_str(rest, &mut output, printer);
+                current_line_len =
+                    line_len_after(current_line_len, width, &output, start, &mut line_breaks);
+            }
+
             Doc::String(s) => {
-                current_line_len += s.len();
-                output.push_str(s);
+                let start = output.len();
+                let width = write_str(s, &mut output, printer);
+                current_line_len =
+                    line_len_after(current_line_len, width, &output, start, &mut line_breaks);
+            }
+
+            Doc::Null => {
+                current_line_len =
+                    current_line_len.saturating_add(write_null(&mut output, printer));
+            }
+
+            Doc::Int(n) => {
+                current_line_len = current_line_len.saturating_add(write_int(*n, &mut output));
+            }
+
+            Doc::UInt(n) => {
+                current_line_len = current_line_len.saturating_add(write_uint(*n, &mut output));
+            }
+
+            Doc::Lazy(text) => {
+                let start = output.len();
+                let width = write_str(text.text(), &mut output, printer);
+                current_line_len =
+                    line_len_after(current_line_len, width, &output, start, &mut line_breaks);
             }
 
+            // Literal text can't break, so there's no need to measure it: emit a concat's
+            // leading literals inline, e.g. the ", " of a ", " + Hardline separator,
+            // rather than pushing each onto the stack only to pop it straight back off.
+            // When breaking long text, it can break, so it's left to the stack.
             Doc::Concat(docs) => {
-                for d in docs.iter().rev() {
-                    stack.push(PrintItem {
-                        doc: d,
-                        indent_delta,
-                    });
+                let literals = if printer.break_long_text {
+                    0
+                } else {
+                    docs.iter().take_while(|d| is_literal(d)).count()
+                };
+                for d in &docs[..literals] {
+                    let start = output.len();
+                    let width = write_literal(d, &mut output, printer);
+                    current_line_len =
+                        line_len_after(current_line_len, width, &output, start, &mut line_breaks);
+                }
+
+                for d in docs[literals..].iter().rev() {
+                    stack.push(item.with(d));
                 }
             }
 
             Doc::Group(d) => {
-                let needs_breaking = count_text_length(d, printer) > printer.max_width;
+                let room = printer.max_width.saturating_sub(current_line_len);
+                let needs_breaking = current_line_len
+                    .saturating_add(capped_width(d, printer, widths, room))
+                    > printer.max_width;
 
                 if needs_breaking {
-                    push_hardline(&mut stack, indent_delta.saturating_sub(printer.indent));
+                    groups_broken += 1;
+                    stack.push(
+                        item.with_indent(&Doc::Hardline, indent_delta.saturating_sub(indent_step)),
+                    );
                 }
 
                 stack.push(PrintItem {
-                    doc: d,
-                    indent_delta,
+                    broken: needs_breaking,
+                    root_broken: root_broken.or(Some(needs_breaking)),
+                    ..item.with(d)
                 });
 
                 if needs_breaking {
-                    push_hardline(&mut stack, indent_delta);
+                    stack.push(item.with(&Doc::Hardline));
+                }
+            }
+
+            // The flattest alternative that fits on the rest of the line, else the first
+            // whose first line does, else the most expanded.
+            Doc::ConditionalGroup(alternatives) => {
+                let room = printer.max_width.saturating_sub(current_line_len);
+                let chosen = match alternatives.split_first() {
+                    Some((flattest, _)) if width_of(flattest, printer, widths) <= room => {
+                        Some(flattest)
+                    }
+                    Some((flattest, rest)) => rest
+                        .iter()
+                        .find(|d| first_line_width(d, printer, widths).0 <= room)
+                        .or(rest.last())
+                        .or(Some(flattest)),
+                    None => None,
+                };
+
+                if let Some(d) = chosen {
+                    stack.push(item.with(d));
                 }
             }
 
@@ -144,71 +1016,365 @@ pub fn pprint<'a>(doc: &'a Doc<'a>, printer: &Printer) -> String {
 
                 let d = if is_or_was_broken { doc } else { other };
 
-                stack.push(PrintItem {
-                    doc: d,
-                    indent_delta,
-                });
+                stack.push(item.with(d));
+            }
+
+            Doc::IfBreakRoot(flat, broken) => {
+                let d = if root_broken.unwrap_or(true) {
+                    broken
+                } else {
+                    flat
+                };
+                stack.push(item.with(d));
+            }
+
+            Doc::Unordered(d) => {
+                stack.push(item.with(d));
             }
 
             Doc::Indent(d) => {
                 stack.push(PrintItem {
-                    doc: d,
-                    indent_delta: indent_delta.saturating_add(printer.indent),
+                    indent_step: printer.indent,
+                    ..item.with_indent(d, indent_delta.saturating_add(printer.indent))
                 });
             }
 
-            Doc::Dedent(d) => {
+            Doc::IndentBy(step, d) => {
                 stack.push(PrintItem {
-                    doc: d,
-                    indent_delta: indent_delta.saturating_sub(printer.indent),
+                    indent_step: *step,
+                    ..item.with_indent(d, indent_delta.saturating_add(*step))
                 });
             }
 
-            Doc::Join(sep, docs) | Doc::SmartJoin(sep, docs) => {
-                let join_fn = if matches!(doc, Doc::SmartJoin(_, _)) {
-                    smart_join_impl
+            Doc::Dedent(d) => {
+                stack.push(item.with_indent(d, indent_delta.saturating_sub(printer.indent)));
+            }
+
+            Doc::Join(sep, docs) | Doc::SmartJoin(sep, docs) | Doc::SmartJoinMax(sep, docs, _) => {
+                let max_per_line = match doc {
+                    Doc::SmartJoinMax(_, _, max_per_line) => Some(*max_per_line),
+                    _ => None,
+                };
+                let joined = if matches!(doc, Doc::Join(_, _)) {
+                    join_impl(sep, docs, printer)
                 } else {
-                    join_impl
+                    smart_join_in(
+                        sep,
+                        docs,
+                        printer,
+                        widths,
+                        current_line_len,
+                        indent_delta,
+                        max_per_line,
+                    )
                 };
 
-                let joined = join_fn(sep, docs, printer);
+                // As with a concat, the leading literals, e.g. all of a short join's items and
+                // separators, are emitted inline, along with the line breaks between them
+                let mut rest = joined.as_slice();
+                while let Some((&d, tail)) = rest.split_first() {
+                    match d {
+                        d if !printer.break_long_text && is_literal(d) => {
+                            let start = output.len();
+                            let width = write_literal(d, &mut output, printer);
+                            current_line_len = line_len_after(
+                                current_line_len,
+                                width,
+                                &output,
+                                start,
+                                &mut line_breaks,
+                            );
+                        }
+                        Doc::Hardline if !printer.collapse_adjacent_lines => {
+                            let start = output.len();
+                            current_line_len = push_newline(
+                                &mut output,
+                                &mut line_breaks,
+                                &mut indentation,
+                                space,
+                                indent_delta,
+                                prefix.as_deref(),
+                            );
+                            last_break = Some((start, output.len()));
+                        }
+                        _ => break,
+                    }
+                    rest = tail;
+                }
 
-                for d in joined.into_iter().rev() {
-                    stack.push(PrintItem {
-                        doc: d,
-                        indent_delta,
-                    });
+                for &d in rest.iter().rev() {
+                    stack.push(item.with(d));
+                }
+            }
+
+            // Each cell is printed independently, then padded to its column's width, that of
+            // its widest line; the last column is left unpadded to avoid trailing whitespace.
+            // A cell's later lines continue at the table's indentation.
+            Doc::Table(rows) => {
+                let cells: Vec<Vec<_>> = rows
+                    .iter()
+                    .map(|row| {
+                        row.iter()
+                            .map(|cell| render(cell, printer, widths))
+                            .collect()
+                    })
+                    .collect();
+
+                let mut column_widths: Vec<usize> = Vec::new();
+                for row in &cells {
+                    for (j, cell) in row.iter().enumerate() {
+                        let width = cell.lines().map(text_width).max().unwrap_or(0);
+                        match column_widths.get_mut(j) {
+                            Some(column_width) => *column_width = (*column_width).max(width),
+                            None => column_widths.push(width),
+                        }
+                    }
+                }
+
+                for (i, row) in cells.iter().enumerate() {
+                    if i > 0 {
+                        current_line_len = push_newline(
+                            &mut output,
+                            &mut line_breaks,
+                            &mut indentation,
+                            space,
+                            indent_delta,
+                            prefix.as_deref(),
+                        );
+                    }
+
+                    for (j, cell) in row.iter().enumerate() {
+                        if j > 0 {
+                            output.push(' ');
+                            current_line_len = current_line_len.saturating_add(1);
+                        }
+
+                        groups_broken += cell.groups_broken;
+                        let mut width = 0;
+                        for (k, line) in cell.lines().enumerate() {
+                            if k > 0 {
+                                current_line_len = push_newline(
+                                    &mut output,
+                                    &mut line_breaks,
+                                    &mut indentation,
+                                    space,
+                                    indent_delta,
+                                    prefix.as_deref(),
+                                );
+                            }
+                            output.push_str(line);
+                            width = text_width(line);
+                            current_line_len = current_line_len.saturating_add(width);
+                        }
+
+                        if j + 1 < row.len() {
+                            let padding = column_widths[j].saturating_sub(width);
+                            output.push_str(&" ".repeat(padding));
+                            current_line_len = current_line_len.saturating_add(padding);
+                        }
+                    }
+                }
+            }
+
+            // A prefixed region starts its lines with the prefix, placed after the
+            // indentation the region starts at, and after any enclosing prefixes.
+            Doc::Prefix(text, d) => {
+                let mut line_start = match prefix {
+                    Some(prefix) => {
+                        prefix.text.clone()
+                            + &space.repeat(indent_delta.saturating_sub(prefix.indent))
+                    }
+                    None => space.repeat(indent_delta),
+                };
+                line_start.push_str(text);
+
+                if at_line_start(&output, prefix.as_deref()) {
+                    current_line_len = current_line_len.saturating_add(text_width(text));
+                    output.push_str(text);
+                }
+
+                stack.push(PrintItem {
+                    prefix: Some(Rc::new(LinePrefix {
+                        indent: indent_delta,
+                        text: line_start,
+                    })),
+                    ..item.with(d)
+                });
+            }
+
+            // Measured as it's printed, so as this printer would, e.g. once escaped
+            Doc::Pad(side, width, d) => {
+                let padding = width.saturating_sub(width_of(d, printer, widths));
+                match side {
+                    PadSide::Left => {
+                        output.push_str(&" ".repeat(padding));
+                        current_line_len = current_line_len.saturating_add(padding);
+                        stack.push(item.with(d));
+                    }
+                    PadSide::Right => {
+                        stack.extend(std::iter::repeat_n(item.with(&PAD_SPACE), padding));
+                        stack.push(item.with(d));
+                    }
                 }
             }
 
             Doc::Line => {
-                current_line_len = 0;
+                if printer.collapse_adjacent_lines {
+                    undo_adjacent_break(&mut output, &mut line_breaks, last_break);
+                }
+                let start = output.len();
+                line_breaks.push(output.len());
                 output.push('\n');
+                current_line_len = match prefix {
+                    Some(prefix) => {
+                        output.push_str(&prefix.text);
+                        text_width(&prefix.text)
+                    }
+                    None => 0,
+                };
+                last_break = Some((start, output.len()));
             }
 
             Doc::Hardline => {
-                let line = hardlines
-                    .entry(indent_delta)
-                    .or_insert_with(|| space.repeat(indent_delta));
+                if printer.collapse_adjacent_lines {
+                    undo_adjacent_break(&mut output, &mut line_breaks, last_break);
+                }
+                let start = output.len();
+                current_line_len = push_newline(
+                    &mut output,
+                    &mut line_breaks,
+                    &mut indentation,
+                    space,
+                    indent_delta,
+                    prefix.as_deref(),
+                );
+                last_break = Some((start, output.len()));
+            }
 
-                output.push('\n');
-                output.push_str(line);
+            Doc::EmptyLine if printer.empty_collection == CollapseStyle::Expanded => {
+                stack.push(item.with(&Doc::Hardline));
+            }
+
+            Doc::GroupLine(_) if broken => {
+                stack.push(item.with(&Doc::Hardline));
+            }
 
-                current_line_len = line.len();
+            Doc::GroupLine(flat) => {
+                stack.push(item.with(flat));
             }
 
-            Doc::Mediumline if current_line_len > printer.max_width / 2 => {
-                push_hardline(&mut stack, indent_delta);
+            Doc::Mediumline
+                if current_line_len as f32
+                    > printer.max_width as f32 * printer.medium_threshold =>
+            {
+                stack.push(item.with(&Doc::Hardline));
             }
 
             Doc::Softline if current_line_len > printer.max_width => {
-                push_hardline(&mut stack, indent_delta);
+                stack.push(item.with(&Doc::Hardline));
+            }
+
+            // Optional spacing is dropped at the start of a line, where it'd only
+            // misalign the indentation.
+            Doc::Space(spacing)
+                if printer.has_spacing(*spacing) && !at_line_start(&output, prefix.as_deref()) =>
+            {
+                current_line_len = current_line_len.saturating_add(1);
+                output.push(' ');
             }
 
             _ => {}
         }
     }
-    output
+    Rendered {
+        output,
+        line_breaks,
+        groups_broken,
+    }
+}
+
+/// Metrics about a rendered document, e.g. for tuning its layout.
+/// Widths are display widths, as the printer measures them; see `text_width`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    pub lines: usize,
+    pub max_line_width: usize,
+    pub bytes: usize,
+    pub groups_broken: usize,
+}
+
+/// As `pprint`, but also returning metrics about the output.
+pub fn pprint_stats<'a>(doc: &'a Doc<'a>, printer: &Printer) -> (String, RenderStats) {
+    let rendered = render_finished(doc, printer, None);
+
+    let stats = RenderStats {
+        lines: rendered.line_breaks.len() + 1,
+        max_line_width: rendered.lines().map(text_width).max().unwrap_or(0),
+        bytes: rendered.output.len(),
+        groups_broken: rendered.groups_broken,
+    };
+    (rendered.output, stats)
+}
+
+/// As `pprint`, but returning the output's lines separately, e.g. for a TUI to draw.
+/// The lines are split where the printer broke them, rather than by searching the output.
+pub fn pprint_lines<'a>(doc: &'a Doc<'a>, printer: &Printer) -> Vec<String> {
+    render_finished(doc, printer, None)
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+/// As `pprint`, but into a `ropey::Rope`, e.g. for an editor buffer that's edited
+/// after being printed into.
+/// This is a conversion of `pprint`'s output, rather than printing into the rope
+/// directly, so the output is held as a `String` in full along the way.
+#[cfg(feature = "ropey")]
+pub fn pprint_rope<'a>(doc: &'a Doc<'a>, printer: &Printer) -> ropey::Rope {
+    ropey::Rope::from_str(&pprint(doc, printer))
+}
+
+/// Pretty print a batch of independent documents.
+/// With the `rayon` feature enabled, the documents are printed in parallel;
+/// either way, the output is identical to printing each in turn.
+pub fn pprint_batch<'a>(docs: &'a [Doc<'a>], printer: &Printer) -> Vec<String> {
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        docs.par_iter().map(|doc| pprint(doc, printer)).collect()
+    }
+
+    #[cfg(not(feature = "rayon"))]
+    {
+        docs.iter().map(|doc| pprint(doc, printer)).collect()
+    }
+}
+
+/// Where `Printer.break_long_text` breaks text that overflows the line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakStyle {
+    /// Between words, leaving words too long for a line of their own whole.
+    Word,
+    /// At exactly the page width, even mid-word, e.g. for URLs and hashes.
+    Char,
+}
+
+/// How `smart_join` chooses where to wrap its lines.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WrapAlgo {
+    /// Minimum raggedness, as in LaTeX: the lines are filled as evenly as possible.
+    MinRaggedness,
+    /// Fill each line as full as it'll go, in a single pass; faster for long sequences.
+    Greedy,
+}
+
+/// How `Printer.empty_collection` renders empty collections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CollapseStyle {
+    /// On one line, `[]`.
+    Inline,
+    /// With the brackets on lines of their own, `[` and `]`.
+    Expanded,
 }
 
 #[derive(Debug, Clone)]
@@ -216,7 +1382,53 @@ pub struct Printer {
     pub max_width: usize,
     pub indent: usize,
     pub break_long_text: bool,
+    /// Where `break_long_text` breaks text: between words, or at exactly the page width.
+    pub long_text_break: BreakStyle,
     pub use_tabs: bool,
+    /// Escape non-ASCII characters in text as `\u{...}`, e.g. for ASCII-only logs.
+    pub ascii_only: bool,
+    /// Print map and struct entries as `key: value`, rather than `key:value`.
+    pub space_after_colon: bool,
+    /// Pad flat collections as `[ 1, 2 ]`, rather than `[1, 2]`.
+    pub space_inside_brackets: bool,
+    /// The fraction of `max_width`, between 0 and 1, past which a `Mediumline` breaks.
+    pub medium_threshold: f32,
+    /// How `smart_join` chooses where to wrap its lines.
+    pub wrap_algorithm: WrapAlgo,
+    /// Measure documents exactly when deciding whether groups break, rather than with
+    /// the usual estimates, e.g. of where smart joins wrap, so that layouts only change
+    /// when the output would. Slower, but steadier for golden tests.
+    pub exact_widths: bool,
+    /// What `Doc::Null` renders as, e.g. `"<null>"` when debugging a document's structure;
+    /// by default it renders as nothing.
+    pub null_display: Option<Cow<'static, str>>,
+    /// What elided output, e.g. a cycle's back-reference, is printed as.
+    /// Defaults to `"…"`; set it to `"..."` for terminals that aren't Unicode-aware.
+    pub ellipsis: Cow<'static, str>,
+    /// Whether empty collections, and structs without fields, stay inline or expand.
+    pub empty_collection: CollapseStyle,
+    /// Sort the entries of unordered collections, i.e. `HashMap`s and `HashSet`s, by key,
+    /// for output that's the same from run to run.
+    pub sort_keys: bool,
+    /// Text prepended to `pprint`'s output, e.g. a UTF-8 BOM or a generated-file header.
+    /// It's written as-is, so doesn't count towards the first line's width.
+    pub prologue: Option<Cow<'static, str>>,
+    /// Text appended to `pprint`'s output, e.g. a trailing newline.
+    pub epilogue: Option<Cow<'static, str>>,
+    /// The most output to print, in bytes, e.g. to guard logging against pathologically
+    /// large values. Longer output is cut short and marked `"… (truncated)"`, with the
+    /// printer's ellipsis.
+    pub max_output_bytes: Option<usize>,
+    /// Number `pprint`'s output lines from 1, in a right-aligned gutter, `  9 | ...`,
+    /// as wide as the last line's number. The gutter isn't counted towards `max_width`.
+    pub line_numbers: bool,
+    /// The character to indent with, e.g. an em space, `'\u{2003}'`, for proportional
+    /// fonts; each indentation level is `indent` of them. `use_tabs` overrides it.
+    pub indent_char: char,
+    /// Collapse line breaks printed one after another, e.g. a `Hardline` that follows a
+    /// broken group's, into one, so as not to leave accidental blank lines. Intended
+    /// blank lines, e.g. `hardlines(2)`, are collapsed too, so this is off by default.
+    pub collapse_adjacent_lines: bool,
 }
 
 /// Default printer configuration.
@@ -224,7 +1436,24 @@ pub const PRINTER: Printer = Printer {
     max_width: 80,
     indent: 2,
     break_long_text: false,
+    long_text_break: BreakStyle::Word,
     use_tabs: false,
+    ascii_only: false,
+    space_after_colon: true,
+    space_inside_brackets: false,
+    medium_threshold: 0.5,
+    wrap_algorithm: WrapAlgo::MinRaggedness,
+    exact_widths: false,
+    null_display: None,
+    ellipsis: Cow::Borrowed("…"),
+    empty_collection: CollapseStyle::Inline,
+    sort_keys: false,
+    prologue: None,
+    epilogue: None,
+    max_output_bytes: None,
+    line_numbers: false,
+    indent_char: ' ',
+    collapse_adjacent_lines: false,
 };
 
 impl Default for Printer {
@@ -233,10 +1462,84 @@ impl Default for Printer {
     }
 }
 
+/// A nonsensical printer configuration, as rejected by `Printer::checked`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PrinterError {
+    ZeroWidth,
+    ZeroIndent,
+    IndentExceedsWidth { indent: usize, max_width: usize },
+    MediumThresholdOutOfRange(f32),
+}
+
+impl std::fmt::Display for PrinterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrinterError::ZeroWidth => write!(f, "max_width must be greater than 0"),
+            PrinterError::ZeroIndent => write!(f, "indent must be greater than 0"),
+            PrinterError::IndentExceedsWidth { indent, max_width } => write!(
+                f,
+                "indent ({}) must not exceed max_width ({})",
+                indent, max_width
+            ),
+            PrinterError::MediumThresholdOutOfRange(threshold) => write!(
+                f,
+                "medium_threshold ({}) must be between 0 and 1",
+                threshold
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PrinterError {}
+
+/// The width of the terminal on stdout, if there is one.
+#[cfg(feature = "terminal_size")]
+fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size().map(|(width, _)| usize::from(width.0))
+}
+
+#[cfg(not(feature = "terminal_size"))]
+fn terminal_width() -> Option<usize> {
+    None
+}
+
 /// A builder for a printer configuration.
 /// Allows for setting the max width, indent, whether to break long text,
-/// and whether to use tabs.
+/// and whether to use tabs; all other options take their default values.
+///
+/// No validation is done: an `indent` of 0 is allowed, in which case `indent()` and
+/// `dedent()` are no-ops and broken documents are printed unindented.
+/// Use `Printer::checked` to reject such configurations instead.
 impl Printer {
+    /// The default configuration, indenting by 2 spaces.
+    pub const TWO_SPACE: Printer = PRINTER;
+
+    /// The default configuration, but indenting by 4 spaces.
+    pub const FOUR_SPACE: Printer = Printer {
+        indent: 4,
+        ..PRINTER
+    };
+
+    /// The default configuration, but as wide as the terminal: `$COLUMNS`, if it's set
+    /// to a positive width, else, with the `terminal_size` feature, the width of the
+    /// terminal on stdout, else the default 80 columns.
+    /// `$NO_COLOR` isn't read, as printers have no color output to disable.
+    pub fn from_env() -> Printer {
+        Printer::from_columns(std::env::var("COLUMNS").ok().as_deref())
+    }
+
+    /// As `from_env`, but taking the value of `$COLUMNS` rather than reading it.
+    pub fn from_columns(columns: Option<&str>) -> Printer {
+        let columns = columns
+            .and_then(|columns| columns.trim().parse().ok())
+            .filter(|&columns| columns > 0);
+
+        Printer {
+            max_width: columns.or_else(terminal_width).unwrap_or(PRINTER.max_width),
+            ..PRINTER
+        }
+    }
+
     pub const fn new(
         max_width: usize,
         indent: usize,
@@ -248,24 +1551,98 @@ impl Printer {
             indent,
             break_long_text,
             use_tabs,
+            ..PRINTER
+        }
+    }
+
+    /// As `Printer::new`, but rejecting nonsensical configurations; see `Printer::validate`.
+    pub fn checked(
+        max_width: usize,
+        indent: usize,
+        break_long_text: bool,
+        use_tabs: bool,
+    ) -> Result<Self, PrinterError> {
+        let printer = Printer::new(max_width, indent, break_long_text, use_tabs);
+        printer.validate()?;
+        Ok(printer)
+    }
+
+    /// Check that the configuration is sensible: the page and indentation must be
+    /// non-empty, a single indent must fit on the page, and `medium_threshold`
+    /// must be a fraction of the page width.
+    pub fn validate(&self) -> Result<(), PrinterError> {
+        if self.max_width == 0 {
+            return Err(PrinterError::ZeroWidth);
+        }
+        if self.indent == 0 {
+            return Err(PrinterError::ZeroIndent);
         }
+        if self.indent > self.max_width {
+            return Err(PrinterError::IndentExceedsWidth {
+                indent: self.indent,
+                max_width: self.max_width,
+            });
+        }
+        if !(0.0..=1.0).contains(&self.medium_threshold) {
+            return Err(PrinterError::MediumThresholdOutOfRange(
+                self.medium_threshold,
+            ));
+        }
+        Ok(())
     }
 
     pub fn pprint<'a>(&self, doc: impl Into<Doc<'a>>) -> String {
         pprint(&doc.into(), self)
     }
+
+    /// Whether the given optional spacing is enabled.
+    pub fn has_spacing(&self, spacing: Spacing) -> bool {
+        match spacing {
+            Spacing::AfterColon => self.space_after_colon,
+            Spacing::InsideBrackets => self.space_inside_brackets,
+            Spacing::Always => true,
+        }
+    }
+
+    /// The display width of the ellipsis, for accounting for elided output.
+    pub fn ellipsis_width(&self) -> usize {
+        text_width(&self.ellipsis)
+    }
+
+    /// A single column of indentation: a tab if `use_tabs` is set, else `indent_char`.
+    fn indent_str(&self) -> String {
+        if self.use_tabs {
+            "\t".to_string()
+        } else {
+            self.indent_char.to_string()
+        }
+    }
+}
+
+impl Doc<'_> {
+    /// Whether the document fits within `width` columns when printed flat,
+    /// as measured by `display_width`.
+    pub fn fits(&self, width: usize, printer: &Printer) -> bool {
+        display_width(self, printer) <= width
+    }
+
+    /// Whether two documents render identically with the default printer,
+    /// regardless of how their trees are shaped. Mostly useful in tests.
+    pub fn structural_eq(&self, other: &Doc) -> bool {
+        pprint(self, &PRINTER) == pprint(other, &PRINTER)
+    }
 }
 
 impl std::fmt::Debug for Doc<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = PRINTER.pprint(self.clone());
+        let s = pprint(self, &PRINTER);
         f.write_str(&s)
     }
 }
 
 impl std::fmt::Display for Doc<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let s = PRINTER.pprint(self.clone());
+        let s = pprint(self, &PRINTER);
         f.write_str(&s)
     }
 }
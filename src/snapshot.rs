@@ -0,0 +1,27 @@
+//! Snapshot testing of documents with `insta`.
+
+#[doc(hidden)]
+pub use insta as __insta;
+
+/// Assert that a document, printed with the default printer, matches its `insta` snapshot.
+/// Takes an optional snapshot name, as `insta::assert_snapshot!` does.
+///
+/// ```no_run
+/// use pprint::{assert_doc_snapshot, Doc};
+///
+/// assert_doc_snapshot!(Doc::from(vec![1, 2, 3]));
+/// assert_doc_snapshot!("numbers", Doc::from(vec![1, 2, 3]));
+/// ```
+#[macro_export]
+macro_rules! assert_doc_snapshot {
+    ($doc:expr $(,)?) => {
+        $crate::assert_doc_snapshot!(None::<&str>, $doc)
+    };
+    ($name:expr, $doc:expr $(,)?) => {
+        $crate::snapshot::__insta::assert_snapshot!(
+            $name,
+            $crate::PRINTER.pprint($doc),
+            stringify!($doc)
+        )
+    };
+}
@@ -1,4 +1,4 @@
-use std::usize;
+use std::borrow::Cow;
 
 /// Text justification algorithm inspired by LaTeX's algorithm.
 ///
@@ -16,7 +16,7 @@ use std::usize;
 /// # Returns
 ///
 /// A vector of indices that represent the end of each line in the justified text.
-pub fn text_justify(sep_length: usize, doc_lengths: &Vec<usize>, max_width: usize) -> Vec<usize> {
+pub fn text_justify(sep_length: usize, doc_lengths: &[usize], max_width: usize) -> Vec<usize> {
     // Score struct to hold the badness and the index of the next word
     #[derive(Clone, Debug)]
     struct Score {
@@ -38,29 +38,32 @@ pub fn text_justify(sep_length: usize, doc_lengths: &Vec<usize>, max_width: usiz
 
     // Iterate over the words in reverse order
     for i in (0..=n).rev() {
-        let mut line_length = 0;
+        let mut line_length: usize = 0;
 
         // For each word, calculate the line length and badness
         for j in i..n {
             // Add the length of the current word to the line length
-            line_length += doc_lengths[j];
+            line_length = line_length.saturating_add(doc_lengths[j]);
             // Add the separator length if this is not the first word in the line
             if j > i {
-                line_length += sep_length;
+                line_length = line_length.saturating_add(sep_length);
             }
             // Ensure that the line length does not exceed the maximum width
             line_length = line_length.min(max_width);
 
             // Calculate the badness as the cube of the unused space at the end of the line
-            let badness = (max_width - line_length).pow(3);
+            // Saturating, as the page may be practically unbounded
+            let gap = max_width - line_length;
+            let badness = gap.saturating_mul(gap).saturating_mul(gap);
             // Get the score of the next word
             let next_score = memo[j + 1].clone();
 
             // If the total badness of this line and the next is less than the current badness,
             // update the score for this word
-            if badness + next_score.badness < memo[i].badness {
+            let total_badness = badness.saturating_add(next_score.badness);
+            if total_badness < memo[i].badness {
                 memo[i] = Score {
-                    badness: badness + next_score.badness,
+                    badness: total_badness,
                     j: j + 1,
                 };
             }
@@ -80,3 +83,129 @@ pub fn text_justify(sep_length: usize, doc_lengths: &Vec<usize>, max_width: usiz
         })
         .collect::<Vec<_>>()
 }
+
+/// Minimum-raggedness line breaking, as used by `smart_join`.
+///
+/// Given the lengths of a sequence of documents, joined by a separator of length `sep_length`,
+/// returns the indices of the documents that start each new line, in increasing order, such
+/// that the lines fit within `max_width` as evenly as possible. The first line always starts
+/// at index 0, which is omitted.
+///
+/// ```
+/// use pprint::line_breaks;
+///
+/// // "aaa bb cc ddddd" at a width of 9: "aaa bb" / "cc ddddd", rather than "aaa bb cc" / "ddddd"
+/// assert_eq!(line_breaks(1, &[3, 2, 2, 5], 9), vec![2]);
+/// ```
+pub fn line_breaks(sep_length: usize, doc_lengths: &[usize], max_width: usize) -> Vec<usize> {
+    // `text_justify` follows the chain of line ends past the last document, so stop there
+    text_justify(sep_length, doc_lengths, max_width)
+        .into_iter()
+        .take_while(|&j| j < doc_lengths.len())
+        .collect()
+}
+
+/// Greedy line breaking, the linear alternative to `line_breaks`.
+///
+/// Fills each line with as many documents as fit within `max_width`, in a single pass,
+/// returning the indices of the documents that start each new line, as `line_breaks` does.
+/// A document too long to fit on a line by itself is given a line of its own.
+///
+/// ```
+/// use pprint::greedy_line_breaks;
+///
+/// // "aaa bb cc ddddd" at a width of 9: "aaa bb cc" / "ddddd"
+/// assert_eq!(greedy_line_breaks(1, &[3, 2, 2, 5], 9), vec![3]);
+/// ```
+pub fn greedy_line_breaks(
+    sep_length: usize,
+    doc_lengths: &[usize],
+    max_width: usize,
+) -> Vec<usize> {
+    let mut breaks = Vec::new();
+    let mut line_length: usize = 0;
+
+    for (i, &length) in doc_lengths.iter().enumerate() {
+        if i > 0 {
+            let extended = line_length
+                .saturating_add(sep_length)
+                .saturating_add(length);
+            if extended > max_width {
+                breaks.push(i);
+                line_length = length;
+            } else {
+                line_length = extended;
+            }
+        } else {
+            line_length = length;
+        }
+    }
+    breaks
+}
+
+/// The number of decimal digits in an integer.
+pub fn digit_count(n: u128) -> usize {
+    n.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// The display width of text, i.e. the columns it takes up on screen: one per character,
+/// except for ANSI escape sequences, e.g. the `\x1b[31m` of colored text, and zero-width
+/// characters, like combining accents and zero-width spaces, which take up none.
+/// Wide characters, e.g. CJK ideographs, are approximated as a single column.
+pub fn text_width(s: &str) -> usize {
+    if s.bytes().all(|b| b.is_ascii() && b != 0x1b) {
+        return s.len();
+    }
+
+    let mut width = 0;
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            // A control sequence, ESC [ parameters... final byte, or a two-character escape
+            '\x1b' => {
+                if chars.next() == Some('[') {
+                    chars.by_ref().find(|c| ('@'..='~').contains(c));
+                }
+            }
+            '\u{0300}'..='\u{036f}'
+            | '\u{200b}'..='\u{200f}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{feff}' => {}
+            _ => width += 1,
+        }
+    }
+    width
+}
+
+/// The length of a string once its non-ASCII characters are escaped by `escape_non_ascii`.
+pub fn escaped_len(s: &str) -> usize {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii() {
+                1
+            } else {
+                // "\u{" + hex digits + "}"
+                let hex_digits = (32 - (c as u32).leading_zeros() as usize).div_ceil(4);
+                4 + hex_digits
+            }
+        })
+        .sum()
+}
+
+/// Escape every non-ASCII character in a string as `\u{...}`.
+/// Borrows the input if it's already pure ASCII.
+pub fn escape_non_ascii(s: &str) -> Cow<'_, str> {
+    if s.is_ascii() {
+        return Cow::Borrowed(s);
+    }
+
+    let mut escaped = String::with_capacity(escaped_len(s));
+    for c in s.chars() {
+        if c.is_ascii() {
+            escaped.push(c);
+        } else {
+            escaped.push_str(&format!("\\u{{{:x}}}", c as u32));
+        }
+    }
+    Cow::Owned(escaped)
+}
@@ -0,0 +1,61 @@
+#[cfg(test)]
+mod tests {
+    use pprint::{pprint, Doc, PRINTER};
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct CountingAlloc;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.with(|n| n.set(n.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOC: CountingAlloc = CountingAlloc;
+
+    /// The number of allocations `f` makes on this thread.
+    fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+        let before = ALLOCATIONS.with(Cell::get);
+        let _ = f();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+
+    #[test]
+    fn test_owned_strings_are_moved() {
+        let s = "hello".repeat(10);
+        assert_eq!(allocations(|| Doc::from(s)), 0);
+
+        let s = Rc::new("hello".repeat(10));
+        assert_eq!(allocations(|| Doc::from(s)), 0);
+
+        // Shared, so it has to be cloned
+        let s = Rc::new("hello".repeat(10));
+        let shared = s.clone();
+        assert_eq!(allocations(|| Doc::from(s)), 1);
+        drop(shared);
+    }
+
+    #[test]
+    fn test_display_doesnt_clone() {
+        let words: Vec<_> = (0..100).map(|i| format!("word{}", i)).collect();
+        let doc = Doc::from(words);
+
+        let printed = allocations(|| pprint(&doc, &PRINTER));
+        let displayed = allocations(|| doc.to_string());
+
+        // Displaying only adds the final copy into the formatter's string
+        assert!(displayed <= printed + 1, "{} > {} + 1", displayed, printed);
+    }
+}
@@ -0,0 +1,123 @@
+#[cfg(test)]
+mod tests {
+    use pprint::{Doc, Pretty, Printer};
+
+    #[derive(Pretty)]
+    #[pprint(verbose)]
+    pub struct Endpoint<'a> {
+        host: &'a str,
+        port: u16,
+    }
+
+    #[derive(Pretty)]
+    #[pprint(verbose)]
+    pub struct Service<'a> {
+        name: &'a str,
+        endpoint: Endpoint<'a>,
+        replicas: Vec<u32>,
+        #[pprint(rename = "healthy?")]
+        healthy: bool,
+    }
+
+    #[derive(Pretty)]
+    #[pprint(verbose)]
+    pub enum Event<'a> {
+        Started,
+        Retried(u32),
+        Moved(i32, i32),
+        Failed { code: u16, reason: &'a str },
+    }
+
+    #[derive(Pretty)]
+    pub struct Pair<T> {
+        left: T,
+        right: T,
+    }
+
+    // Render at a narrow, the default, and a wide page, to pin the derived layouts
+    fn render<'a>(doc: impl Into<Doc<'a>>) -> [String; 3] {
+        let doc = doc.into();
+        [20, 80, 200].map(|max_width| {
+            Printer {
+                max_width,
+                ..Printer::default()
+            }
+            .pprint(doc.clone())
+        })
+    }
+
+    fn service() -> Service<'static> {
+        Service {
+            name: "api",
+            endpoint: Endpoint {
+                host: "localhost",
+                port: 8080,
+            },
+            replicas: vec![1, 2, 3],
+            healthy: true,
+        }
+    }
+
+    fn events() -> Vec<Event<'static>> {
+        vec![
+            Event::Started,
+            Event::Retried(3),
+            Event::Moved(-1, 2),
+            Event::Failed {
+                code: 503,
+                reason: "unavailable",
+            },
+        ]
+    }
+
+    #[test]
+    fn test_nested_structs() {
+        assert_eq!(
+            render(service()),
+            [
+                "Service {\n  name: api,\n  endpoint: Endpoint {\n    host: localhost,\n    port: 8080\n  },\n  replicas: [1, 2, 3],\n  healthy?: true\n}",
+                "Service {\n  name: api,\n  endpoint: Endpoint {host: localhost, port: 8080},\n  replicas: [1, 2, 3],\n  healthy?: true\n}",
+                "Service {name: api, endpoint: Endpoint {host: localhost, port: 8080}, replicas: [1, 2, 3], healthy?: true}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enum_field_kinds() {
+        assert_eq!(
+            render(events()),
+            [
+                "[\n  Started, \n  Retried(3), \n  Moved(-1, 2), \n  Failed(\n    503, unavailable\n  )\n]",
+                "[\n  Started, Retried(3), Moved(-1, 2), \n  Failed(503, unavailable)\n]",
+                "[Started, Retried(3), Moved(-1, 2), Failed(503, unavailable)]",
+            ]
+        );
+    }
+
+    // Without a verbose header, each field always gets a line of its own
+    #[test]
+    fn test_generics() {
+        assert_eq!(
+            render(Pair {
+                left: vec![1, 2],
+                right: vec![3],
+            }),
+            [
+                "{\n  left: [1, 2], \n  right: [3]\n}",
+                "{\n  left: [1, 2], \n  right: [3]\n}",
+                "{\n  left: [1, 2], \n  right: [3]\n}",
+            ]
+        );
+        assert_eq!(
+            render(vec![Pair {
+                left: "a",
+                right: "b",
+            }]),
+            [
+                "[\n  {\n    left: a, \n    right: b\n  }\n]",
+                "[\n  {\n    left: a, \n    right: b\n  }\n]",
+                "[\n  {\n    left: a, \n    right: b\n  }\n]",
+            ]
+        );
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use pprint::{Pretty, Printer};
+    use pprint::{CollapseStyle, Pretty, Printer};
 
     use std::collections::HashMap;
 
@@ -56,6 +56,201 @@ mod tests {
         println!("{}", pprint);
     }
 
+    #[test]
+    fn test_struct_spacing() {
+        let printer = Printer {
+            space_after_colon: false,
+            ..Printer::default()
+        };
+
+        let s = InnerStrumct {
+            x: "hello",
+            y: HeyEnum::A,
+            z: (1, 2),
+        };
+
+        let pprint = printer.pprint(s);
+        assert!(pprint.contains("x:hello"));
+        assert!(pprint.contains("y:MyEnum::A"));
+    }
+
+    #[test]
+    fn test_verbose_struct_width() {
+        let s = || InnerStrumct {
+            x: "hello",
+            y: HeyEnum::A,
+            z: (1, 2),
+        };
+
+        let pprint = Printer::default().pprint(s());
+        assert_eq!(pprint, "Inner {x: hello, y: MyEnum::A, z: (1, 2)}");
+
+        let printer = Printer {
+            max_width: 20,
+            ..Printer::default()
+        };
+        let pprint = printer.pprint(s());
+        assert_eq!(
+            pprint,
+            "Inner {\n  x: hello,\n  y: MyEnum::A,\n  z: (1, 2)\n}"
+        );
+    }
+
+    #[derive(Pretty)]
+    pub struct Marker;
+
+    #[derive(Pretty)]
+    #[pprint(rename = "Renamed")]
+    pub struct RenamedMarker;
+
+    #[test]
+    fn test_unit() {
+        let printer = Printer::default();
+
+        assert_eq!(printer.pprint(Marker), "Marker");
+        assert_eq!(printer.pprint(RenamedMarker), "Renamed");
+        assert_eq!(printer.pprint(HeyEnum::A), "MyEnum::A");
+        assert_eq!(printer.pprint(()), "()");
+    }
+
+    #[derive(Pretty, Clone, Copy)]
+    #[pprint(cache_units, discriminant)]
+    pub enum Opcode {
+        Nop,
+        Load = 4,
+        #[pprint(rename = "STORE")]
+        Store,
+        #[pprint(indent)]
+        Jump,
+    }
+
+    #[test]
+    fn test_cache_units() {
+        let printer = Printer::default();
+        let opcodes = [Opcode::Nop, Opcode::Load, Opcode::Store, Opcode::Jump];
+
+        // Rendered the same from the cache as when first built
+        for _ in 0..2 {
+            let pprints: Vec<_> = opcodes.iter().map(|&op| printer.pprint(op)).collect();
+            assert_eq!(pprints, ["Nop = 0", "Load = 4", "STORE = 5", "Jump = 6"]);
+        }
+    }
+
+    #[derive(Pretty)]
+    pub struct Empty {}
+
+    #[derive(Pretty)]
+    #[pprint(verbose)]
+    pub struct VerboseEmpty {}
+
+    #[test]
+    fn test_empty_struct() {
+        let printer = Printer::default();
+        assert_eq!(printer.pprint(Empty {}), "{}");
+        assert_eq!(printer.pprint(VerboseEmpty {}), "VerboseEmpty {}");
+
+        let printer = Printer {
+            empty_collection: CollapseStyle::Expanded,
+            ..Printer::default()
+        };
+        assert_eq!(printer.pprint(Empty {}), "{\n}");
+        assert_eq!(printer.pprint(VerboseEmpty {}), "VerboseEmpty {\n}");
+    }
+
+    #[derive(Pretty)]
+    #[pprint(discriminant)]
+    pub enum Color {
+        Red = 1,
+        Green,
+        #[pprint(rename = "BLUE")]
+        Blue = 1 << 4,
+    }
+
+    #[derive(Pretty)]
+    #[pprint(discriminant)]
+    #[repr(u8)]
+    pub enum Shape {
+        Point = 0,
+        Circle(u32) = 8,
+    }
+
+    #[test]
+    fn test_discriminant() {
+        let printer = Printer::default();
+
+        assert_eq!(printer.pprint(Color::Red), "Red = 1");
+        assert_eq!(printer.pprint(Color::Green), "Green = 2");
+        assert_eq!(printer.pprint(Color::Blue), "BLUE = 16");
+
+        assert_eq!(printer.pprint(Shape::Point), "Point = 0");
+        assert_eq!(printer.pprint(Shape::Circle(3)), "3");
+    }
+
+    #[derive(Pretty)]
+    #[pprint(verbose)]
+    pub struct Tagged<T> {
+        id: u32,
+        _tag: std::marker::PhantomData<T>,
+    }
+
+    #[test]
+    fn test_phantom_data() {
+        let printer = Printer::default();
+
+        let s = Tagged::<String> {
+            id: 7,
+            _tag: std::marker::PhantomData,
+        };
+        assert_eq!(printer.pprint(s), "Tagged {id: 7}");
+
+        assert_eq!(printer.pprint(std::marker::PhantomData::<u8>), "");
+    }
+
+    #[derive(Pretty)]
+    #[pprint(verbose)]
+    pub struct Span {
+        bytes: std::ops::Range<usize>,
+        lines: std::ops::RangeInclusive<u32>,
+    }
+
+    #[test]
+    fn test_range_fields() {
+        let printer = Printer::default();
+
+        let s = Span {
+            bytes: 4..16,
+            lines: 1..=3,
+        };
+        assert_eq!(printer.pprint(s), "Span {bytes: 4..16, lines: 1..=3}");
+
+        assert_eq!(printer.pprint(-2..2), "-2..2");
+        assert_eq!(printer.pprint(0.5..1.5), "0.5..1.5");
+    }
+
+    #[derive(Pretty)]
+    #[pprint(verbose)]
+    pub struct Point(i32, #[pprint(skip)] i32, i32);
+
+    #[derive(Pretty)]
+    pub struct Pair(u8, u8);
+
+    #[derive(Pretty)]
+    #[pprint(verbose)]
+    pub struct Meters<T>(T);
+
+    #[derive(Pretty)]
+    pub struct Celsius(f64);
+
+    #[test]
+    fn test_tuple_structs() {
+        let printer = Printer::default();
+
+        assert_eq!(printer.pprint(Point(1, 0, 2)), "Point(1, 2)");
+        assert_eq!(printer.pprint(Pair(3, 4)), "(3, 4)");
+        assert_eq!(printer.pprint(Meters(5)), "Meters(5)");
+        assert_eq!(printer.pprint(Celsius(21.5)), "21.5");
+    }
+
     #[test]
     fn test_complex_struct() {
         let printer = Printer::default();
@@ -85,3 +280,87 @@ mod tests {
         println!("{}", pprint);
     }
 }
+
+#[cfg(test)]
+mod yaml_tests {
+    use pprint::{Pretty, Printer};
+
+    #[derive(Pretty)]
+    #[pprint(format = "yaml")]
+    pub struct Limits {
+        cpu: u32,
+        memory: u32,
+    }
+
+    #[derive(Pretty)]
+    #[pprint(format = "yaml")]
+    pub struct Server<'a> {
+        host: &'a str,
+        port: u16,
+        tags: Vec<&'a str>,
+        limits: Limits,
+        #[pprint(skip)]
+        _secret: &'a str,
+    }
+
+    #[test]
+    fn test_yaml_struct() {
+        let printer = Printer::default();
+
+        let s = Server {
+            host: "localhost",
+            port: 8080,
+            tags: vec!["web", "api"],
+            limits: Limits {
+                cpu: 2,
+                memory: 512,
+            },
+            _secret: "hunter2",
+        };
+
+        let pprint = printer.pprint(s);
+        assert_eq!(
+            pprint,
+            "host: localhost\nport: 8080\ntags:\n  - web\n  - api\nlimits:\n  cpu: 2\n  memory: 512"
+        );
+    }
+
+    fn count(tags: &[&str]) -> usize {
+        tags.len()
+    }
+
+    #[derive(Pretty)]
+    #[pprint(format = "yaml")]
+    pub struct Tagged<'a> {
+        #[pprint(getter = "count", rename = "tag_count")]
+        tags: Vec<&'a str>,
+        #[pprint(indent)]
+        aliases: Vec<&'a str>,
+    }
+
+    #[test]
+    fn test_yaml_field_attributes() {
+        let printer = Printer::default();
+
+        let s = Tagged {
+            tags: vec!["web", "api"],
+            aliases: vec!["www", "app"],
+        };
+        assert_eq!(
+            printer.pprint(s),
+            "tag_count: 2\naliases:\n    - www\n    - app"
+        );
+    }
+
+    #[derive(Pretty)]
+    #[pprint(format = "yaml")]
+    pub struct Endpoints<'a>(&'a str, &'a str);
+
+    #[test]
+    fn test_yaml_tuple_struct() {
+        let printer = Printer::default();
+
+        let pprint = printer.pprint(Endpoints("localhost:80", "localhost:443"));
+        assert_eq!(pprint, "- localhost:80\n- localhost:443");
+    }
+}
@@ -0,0 +1,920 @@
+#[cfg(test)]
+mod tests {
+    use pprint::{
+        blank_lines, bytes, concat, dedent, display_width, enumerate_doc, error_chain, flags, flat,
+        group, group_if, hardlines, if_break, indent, join, lazy, lines, lines_from, list,
+        map_from, map_from_pairs, mutex, pad_left, pad_right, prefix_lines, set_from,
+        smart_join_max, spaces, table, template, verbatim, with_comment, zip_join, Doc, Printer,
+    };
+    use std::cell::{Cell, RefCell};
+    use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+    use std::ffi::{OsStr, OsString};
+    use std::fmt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_zip_join() {
+        let printer = Printer::default();
+
+        let doc = zip_join(vec!["a", "b"], vec![1, 2], " = ", Doc::Hardline);
+
+        let pprint = printer.pprint(doc);
+        assert_eq!(pprint, "a = 1\nb = 2");
+    }
+
+    #[test]
+    fn test_zip_join_unequal() {
+        let printer = Printer::default();
+
+        let doc = zip_join(vec!["a", "b", "c"], vec![1], " = ", ", ");
+
+        let pprint = printer.pprint(doc);
+        assert_eq!(pprint, "a = 1");
+    }
+
+    #[test]
+    fn test_join_trait() {
+        use pprint::{Join, SmartJoin};
+
+        let strings = vec!["a".to_string(), "b".to_string()];
+        assert_eq!(log(strings.clone().join(", ")), "a, b");
+        assert_eq!(log(strings.smart_join(", ")), "a, b");
+
+        assert_eq!(log(vec![1, 2, 3].join(" + ")), "1 + 2 + 3");
+    }
+
+    #[test]
+    fn test_wrap_methods() {
+        use pprint::{Group, Join, Wrap};
+
+        assert_eq!(log(Doc::from("a").braces()), "{a}");
+        assert_eq!(log(Doc::from("a").brackets()), "[a]");
+        assert_eq!(log(Doc::from("a").parens()), "(a)");
+        assert_eq!(log(Doc::from("a").quoted()), "\"a\"");
+
+        let items = vec![1, 2, 3].join(", ").group().brackets();
+        assert_eq!(
+            items,
+            Doc::from("[") + group(join(", ", vec![1, 2, 3])) + Doc::from("]")
+        );
+        assert_eq!(log(items), "[1, 2, 3]");
+    }
+
+    #[test]
+    fn test_group_if() {
+        use pprint::Group;
+
+        let doc = || Doc::from("a") + Doc::Softline;
+
+        assert_eq!(group_if(true, doc()), group(doc()));
+        assert_eq!(group_if(false, doc()), doc());
+        assert_eq!(doc().group_if(true), group(doc()));
+        assert_eq!(doc().group_if(false), doc());
+    }
+
+    #[test]
+    fn test_flat() {
+        let printer = Printer {
+            max_width: 10,
+            ..Printer::default()
+        };
+        let doc = || Doc::from(vec![vec![1, 2], vec![3, 4]]);
+
+        assert_eq!(printer.pprint(doc()), "[\n  [1, 2], \n  [3, 4]\n]");
+        assert_eq!(printer.pprint(flat(doc())), "[[1, 2], [3, 4]]");
+
+        // Only the flattened subtree is kept on one line
+        let doc = Doc::from(vec![flat(doc()), doc()]);
+        assert_eq!(
+            printer.pprint(doc),
+            "[\n  [[1, 2], [3, 4]], \n  [\n    [1, 2], \n    [3, 4]\n  ]\n]"
+        );
+    }
+
+    #[test]
+    fn test_doc_builder() {
+        use pprint::DocBuilder;
+
+        let built = DocBuilder::new()
+            .text("config:")
+            .indent(|b| {
+                b.line().text("name: api");
+                b.line().text("ports:").indent(|b| {
+                    b.line().doc(80).line().doc(443);
+                });
+            })
+            .line()
+            .text("end")
+            .build();
+
+        let combined = concat(vec![
+            Doc::from("config:"),
+            indent(concat(vec![
+                Doc::Hardline,
+                Doc::from("name: api"),
+                Doc::Hardline,
+                Doc::from("ports:"),
+                indent(concat(vec![
+                    Doc::Hardline,
+                    Doc::from(80),
+                    Doc::Hardline,
+                    Doc::from(443),
+                ])),
+            ])),
+            Doc::Hardline,
+            Doc::from("end"),
+        ]);
+
+        assert_eq!(built, combined);
+        assert_eq!(
+            log(built),
+            "config:\n  name: api\n  ports:\n    80\n    443\nend"
+        );
+    }
+
+    #[test]
+    fn test_structural_eq() {
+        let a = concat(vec!["a", ", ", "b"]);
+        let b = join(", ", vec!["a", "b"]);
+
+        assert_ne!(a, b);
+        assert!(a.structural_eq(&b));
+        assert!(!a.structural_eq(&Doc::from("a, c")));
+    }
+
+    #[test]
+    fn test_table() {
+        let printer = Printer::default();
+
+        let doc = table(vec![
+            vec![Doc::from("name"), Doc::from("age"), Doc::from("city")],
+            vec![Doc::from("alice"), Doc::from(30), Doc::from("nyc")],
+        ]);
+
+        let pprint = printer.pprint(indent(Doc::from("people:") + Doc::Hardline + doc));
+        assert_eq!(pprint, "people:\n  name  age city\n  alice 30  nyc");
+
+        // Multi-line cells continue at the table's indentation, and widen their column
+        // only as far as their widest line
+        let doc = table(vec![
+            vec![
+                Doc::from("x") + Doc::Hardline + Doc::from("xxxx"),
+                Doc::from(1),
+            ],
+            vec![Doc::from("yy"), Doc::from(2)],
+        ]);
+        let pprint = printer.pprint(indent(Doc::from("rows:") + Doc::Hardline + doc));
+        assert_eq!(pprint, "rows:\n  x\n  xxxx 1\n  yy   2");
+    }
+
+    #[test]
+    fn test_to_sexpr() {
+        let doc = group(concat(vec![
+            Doc::from("a"),
+            Doc::Hardline,
+            indent(join(", ", vec!["b", "c\""])),
+        ]));
+
+        assert_eq!(
+            doc.to_sexpr(),
+            r#"(group (concat "a" hardline (indent (join ", " "b" "c\""))))"#
+        );
+    }
+
+    #[test]
+    fn test_prefix_lines() {
+        let printer = Printer::default();
+
+        let quote = prefix_lines(
+            "> ",
+            Doc::from("first") + indent(Doc::Hardline + Doc::from("second")),
+        );
+        let doc = indent(concat(vec![
+            Doc::from("quote:"),
+            Doc::Hardline,
+            quote,
+            Doc::Hardline,
+            Doc::from("done"),
+        ]));
+
+        let pprint = printer.pprint(doc);
+        assert_eq!(pprint, "quote:\n  > first\n  >   second\n  done");
+    }
+
+    #[test]
+    fn test_nested_prefix_lines() {
+        let printer = Printer::default();
+
+        let doc = prefix_lines(
+            "> ",
+            concat(vec![
+                Doc::from("a"),
+                Doc::Hardline,
+                prefix_lines("> ", Doc::from("b") + Doc::Hardline + Doc::from("c")),
+            ]),
+        );
+
+        let pprint = printer.pprint(doc);
+        assert_eq!(pprint, "> a\n> > b\n> > c");
+    }
+
+    #[test]
+    fn test_verbatim() {
+        let printer = Printer::default();
+
+        let block = "fn main() {\n    run();\n}";
+        let doc = indent(concat(vec![
+            Doc::from("code:"),
+            Doc::Hardline,
+            verbatim(block),
+        ]));
+
+        let pprint = printer.pprint(doc);
+        assert_eq!(pprint, "code:\n  fn main() {\n      run();\n  }");
+
+        let pprint = printer.pprint(indent(Doc::Hardline + Doc::from(block)));
+        assert_eq!(pprint, "\n  fn main() {\n    run();\n}");
+    }
+
+    fn log<'a>(value: impl Into<Doc<'a>>) -> String {
+        Printer::default().pprint(value)
+    }
+
+    #[test]
+    fn test_from_references_and_pointers() {
+        let mut values = vec![1, 2, 3];
+        let values_mut: &mut Vec<i32> = &mut values;
+
+        assert_eq!(log(values_mut), "[1, 2, 3]");
+        values.push(4);
+        assert_eq!(log(&values), "[1, 2, 3, 4]");
+
+        assert_eq!(log(std::rc::Rc::new(values.clone())), "[1, 2, 3, 4]");
+        assert_eq!(log(std::sync::Arc::new(values)), "[1, 2, 3, 4]");
+    }
+
+    #[test]
+    fn test_boxed_and_cow_slices() {
+        use std::borrow::Cow;
+
+        let boxed: Box<[i32]> = Box::new([1, 2, 3]);
+        assert_eq!(log(boxed), "[1, 2, 3]");
+        assert_eq!(log(Box::<[i32]>::from([])), "[]");
+
+        let values = [4, 5];
+        assert_eq!(log(Cow::Borrowed(&values[..])), "[4, 5]");
+        assert_eq!(log(Cow::<[i32]>::Owned(vec![])), "[]");
+    }
+
+    #[test]
+    fn test_generic_cow() {
+        use std::borrow::Cow;
+
+        let values = vec![1, 2, 3];
+        let borrowed: Cow<[i32]> = Cow::Borrowed(&values);
+        let owned: Cow<[i32]> = Cow::Owned(values.clone());
+        assert_eq!(log(borrowed), "[1, 2, 3]");
+        assert_eq!(log(owned), "[1, 2, 3]");
+
+        let text = String::from("borrowed");
+        let doc = Doc::from(Cow::Borrowed(text.as_str()));
+        assert!(matches!(doc, Doc::String(Cow::Borrowed(s)) if s.as_ptr() == text.as_ptr()));
+
+        assert_eq!(log(Cow::Borrowed(&7)), "7");
+        assert_eq!(log(Cow::<OsStr>::Owned(OsString::from("path"))), "path");
+    }
+
+    #[test]
+    fn test_lazy() {
+        let calls = AtomicUsize::new(0);
+        let expensive = || {
+            lazy(|| {
+                calls.fetch_add(1, Ordering::Relaxed);
+                "computed".to_string()
+            })
+        };
+
+        assert_eq!(log(Doc::from("value: ") + expensive()), "value: computed");
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        // Only printed where the line breaks, which it doesn't
+        calls.store(0, Ordering::Relaxed);
+        let doc = Doc::from("(") + if_break(expensive(), Doc::from("short")) + Doc::from(")");
+        assert_eq!(log(doc), "(short)");
+        assert_eq!(calls.load(Ordering::Relaxed), 0);
+
+        // Measured at its full width, though still computed only once
+        let fifty = || lazy(|| "x".repeat(50));
+        let doc = Doc::from(vec![fifty(), fifty()]);
+        assert_eq!(
+            log(doc),
+            format!("[\n  {}, \n  {}\n]", "x".repeat(50), "x".repeat(50))
+        );
+
+        calls.store(0, Ordering::Relaxed);
+        assert_eq!(log(Doc::from(vec![expensive()])), "[computed]");
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_pad() {
+        let rows: Vec<_> = [1, -42, 1234, 123456, 1234567]
+            .into_iter()
+            .map(|n| pad_left(Doc::from(n), 6))
+            .collect();
+        assert_eq!(log(lines(rows)), "     1\n   -42\n  1234\n123456\n1234567");
+
+        assert_eq!(log(pad_right(Doc::from("ab"), 4) + Doc::from("|")), "ab  |");
+        assert_eq!(log(pad_right(Doc::from("abcde"), 4)), "abcde");
+
+        // Measured as the printer prints the document, here once escaped
+        let printer = Printer {
+            ascii_only: true,
+            ..Printer::default()
+        };
+        let cells = lines(vec![
+            pad_left(Doc::from("é"), 8),
+            pad_right(Doc::from("é"), 8) + Doc::from("|"),
+            with_comment(Doc::from("é"), Doc::from("# e"), 8),
+        ]);
+        assert_eq!(printer.pprint(cells), "  \\u{e9}\n\\u{e9}  |\n\\u{e9}  # e");
+    }
+
+    #[test]
+    fn test_simplify() {
+        let x = || Doc::from("x") + Doc::Hardline + Doc::from("y");
+
+        assert_eq!(group(group(x())).simplify(), group(group(x())));
+        assert_eq!(indent(dedent(x())).simplify(), x());
+        assert_eq!(dedent(indent(x())).simplify(), dedent(indent(x())));
+        assert_eq!(concat(vec![x()]).simplify(), x());
+        assert_eq!(concat(vec![concat(vec!["a"])]).simplify(), Doc::from("a"));
+
+        // Bottom up, so unwrapping one level exposes the next
+        let doc = group(concat(vec![indent(concat(vec![dedent(group(x()))]))]));
+        assert_eq!(doc.clone().simplify(), group(group(x())));
+
+        let docs = vec![
+            doc,
+            Doc::from(vec![vec![1, 2], vec![3]]),
+            indent(dedent(
+                Doc::from("a") + Doc::Hardline + indent(group(group(x()))),
+            )),
+            smart_join_max(", ", vec![concat(vec![1]), concat(vec![2])], 1),
+        ];
+        for doc in docs {
+            assert_eq!(log(doc.clone().simplify()), log(doc));
+        }
+    }
+
+    #[test]
+    fn test_single_element_tuple() {
+        assert_eq!(log((1,)), "(1,)");
+        assert_eq!(log(("a", (2,))), "(a, (2,))");
+        assert_eq!(log(vec![(1,), (2,)]), "[(1,), (2,)]");
+    }
+
+    #[test]
+    fn test_large_tuples() {
+        let tuple = (
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, "fifteen", 16.5,
+        );
+        let items: Vec<Doc> = vec![
+            1.into(),
+            2.into(),
+            3.into(),
+            4.into(),
+            5.into(),
+            6.into(),
+            7.into(),
+            8.into(),
+            9.into(),
+            10.into(),
+            11.into(),
+            12.into(),
+            13.into(),
+            14.into(),
+            "fifteen".into(),
+            16.5.into(),
+        ];
+        let as_vec = log(items);
+        assert_eq!(log(tuple), format!("({})", &as_vec[1..as_vec.len() - 1]));
+    }
+
+    #[cfg(feature = "large-tuples")]
+    #[test]
+    fn test_tuples_beyond_sixteen() {
+        let tuple = (
+            1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24,
+            25, 26, 27, 28, 29, 30, 31, 32,
+        );
+        let as_vec = log((1..=32).collect::<Vec<_>>());
+        assert_eq!(log(tuple), format!("({})", &as_vec[1..as_vec.len() - 1]));
+    }
+
+    #[test]
+    fn test_flags() {
+        const NAMES: &[(u64, &str)] = &[(0x1, "READ"), (0x2, "WRITE"), (0x4, "EXEC"), (0x3, "RW")];
+
+        assert_eq!(log(flags(0x1, NAMES)), "READ");
+        assert_eq!(log(flags(0x5, NAMES)), "READ | EXEC");
+        assert_eq!(log(flags(0x43, NAMES)), "READ | WRITE | RW | 0x40");
+        assert_eq!(log(flags(0x40, NAMES)), "0x40");
+        assert_eq!(log(flags(0, NAMES)), "0x0");
+    }
+
+    #[test]
+    fn test_template() {
+        let args = [Doc::from("port"), Doc::from(vec![80, 443])];
+        let doc = template("{} = {};", &args);
+        assert_eq!(log(doc.clone()), "port = [80, 443];");
+        assert_eq!(
+            doc,
+            concat(vec![
+                Doc::from("port"),
+                Doc::from(" = "),
+                Doc::from(vec![80, 443]),
+                Doc::from(";"),
+            ])
+        );
+
+        assert_eq!(log(template("{{{}}}", &args[..1])), "{port}");
+        assert_eq!(log(template("{} and {}", &args[..1])), "port and {}");
+        assert_eq!(log(template("no placeholders", &[])), "no placeholders");
+    }
+
+    #[test]
+    fn test_format_args() {
+        let (a, b) = (1, "two");
+        assert_eq!(log(format_args!("{} {}", a, b)), "1 two");
+        assert_eq!(
+            Doc::from(format_args!("{}-{:?}", a, b)),
+            Doc::from("1-\"two\"")
+        );
+
+        let doc = Doc::from(format_args!("literal"));
+        assert!(matches!(
+            doc,
+            Doc::String(std::borrow::Cow::Borrowed("literal"))
+        ));
+    }
+
+    #[test]
+    fn test_interior_mutability() {
+        assert_eq!(log(RefCell::new(vec![1, 2])), "[1, 2]");
+        assert_eq!(log(Cell::new(3)), "3");
+        assert_eq!(log(Mutex::new("four")), "four");
+
+        let shared = Arc::new(Mutex::new(5));
+        assert_eq!(log(mutex(&shared)), "5");
+        let guard = shared.lock().unwrap();
+        assert_eq!(log(mutex(&shared)), "<locked>");
+        drop(guard);
+
+        // Poisoned by a panic while locked, but still readable
+        let poisoner = shared.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poisoned");
+        })
+        .join();
+        assert!(shared.is_poisoned());
+        assert_eq!(log(mutex(&shared)), "5");
+    }
+
+    #[test]
+    fn test_os_str() {
+        let s = OsStr::new("path/to/file.txt");
+        assert_eq!(log(s), "path/to/file.txt");
+        assert!(matches!(
+            Doc::from(s),
+            Doc::String(std::borrow::Cow::Borrowed(_))
+        ));
+        assert_eq!(log(OsString::from("file.txt")), "file.txt");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_os_str_lossy() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let s = OsStr::from_bytes(b"file\xff.txt");
+        assert_eq!(log(s), "file\u{fffd}.txt");
+        assert_eq!(log(s.to_owned()), "file\u{fffd}.txt");
+    }
+
+    #[test]
+    fn test_non_zero() {
+        let value = std::num::NonZeroU64::new(42).unwrap();
+        assert_eq!(log(value), "42");
+        assert_eq!(log(vec![value, value]), "[42, 42]");
+    }
+
+    #[test]
+    fn test_list() {
+        let doc = || list("[", vec![1, 2, 3], ",", "]");
+
+        assert_eq!(Printer::default().pprint(doc()), "[1, 2, 3]");
+
+        let printer = Printer {
+            max_width: 8,
+            ..Printer::default()
+        };
+        assert_eq!(printer.pprint(doc()), "[\n  1,\n  2,\n  3,\n]");
+
+        assert_eq!(log(list("(", Vec::<Doc>::new(), ",", ")")), "()");
+    }
+
+    #[test]
+    fn test_map_from_pairs() {
+        let pairs = vec![("zebra", 1), ("apple", 2), ("mango", 3)];
+        assert_eq!(
+            log(map_from_pairs(pairs)),
+            "{\n  zebra: 1, \n  apple: 2, \n  mango: 3\n}"
+        );
+
+        assert_eq!(log(map_from_pairs(Vec::<(&str, i32)>::new())), "{}");
+    }
+
+    #[test]
+    fn test_with_comment() {
+        let lines = vec![
+            ("name", "\"pprint\"", "# the crate's name"),
+            ("version", "\"0.1.0\"", "# bumped on release"),
+            ("a_very_long_key_name", "\"value\"", "# overflows"),
+        ];
+        let doc = join(
+            Doc::Hardline,
+            lines
+                .into_iter()
+                .map(|(key, value, comment)| {
+                    with_comment(
+                        Doc::from(key) + Doc::from(" = ") + Doc::from(value),
+                        Doc::from(comment),
+                        30,
+                    )
+                })
+                .collect(),
+        );
+        assert_eq!(
+            log(doc),
+            "name = \"pprint\"               # the crate's name\n\
+             version = \"0.1.0\"             # bumped on release\n\
+             a_very_long_key_name = \"value\" # overflows"
+        );
+    }
+
+    #[test]
+    fn test_borrowed_map_keys() {
+        use std::borrow::Cow;
+
+        fn borrowed_strs<'d>(doc: &'d Doc, found: &mut Vec<&'d str>) {
+            match doc {
+                Doc::String(Cow::Borrowed(s)) => found.push(s),
+                Doc::Concat(docs) => docs.iter().for_each(|d| borrowed_strs(d, found)),
+                Doc::Group(d) | Doc::Indent(d) | Doc::Unordered(d) => borrowed_strs(d, found),
+                Doc::Join(sep, docs) | Doc::SmartJoin(sep, docs) => {
+                    borrowed_strs(sep, found);
+                    docs.iter().for_each(|d| borrowed_strs(d, found));
+                }
+                _ => {}
+            }
+        }
+
+        let keys = [String::from("alpha"), String::from("beta")];
+        let map: HashMap<&str, i32> = keys.iter().map(String::as_str).zip(1..).collect();
+        let doc = Doc::from(map);
+
+        // The keys are borrowed from the strings they were built from, rather than copied
+        let mut found = Vec::new();
+        borrowed_strs(&doc, &mut found);
+        for key in &keys {
+            assert!(found.iter().any(|s| s.as_ptr() == key.as_ptr()));
+        }
+    }
+
+    #[test]
+    fn test_enumerate_doc() {
+        assert_eq!(log(enumerate_doc(["a", "b", "c"])), "[0: a, 1: b, 2: c]");
+        assert_eq!(
+            log(enumerate_doc(vec![vec![1], vec![2, 3]])),
+            "[0: [1], 1: [2, 3]]"
+        );
+        assert_eq!(log(enumerate_doc(Vec::<i32>::new())), "[]");
+    }
+
+    #[test]
+    fn test_map_from_set_from() {
+        let pairs = vec![("zebra", 1), ("apple", 2)];
+        assert_eq!(log(map_from(pairs.clone())), log(map_from_pairs(pairs)));
+        assert_eq!(
+            log(map_from([("b", 1), ("a", 2)])),
+            "{\n  b: 1, \n  a: 2\n}"
+        );
+        assert_eq!(
+            log(map_from(BTreeMap::from([("b", 1), ("a", 2)]))),
+            "{\n  a: 2, \n  b: 1\n}"
+        );
+        assert_eq!(log(map_from(Vec::<(&str, i32)>::new())), "{}");
+
+        assert_eq!(log(set_from([3, 1, 2])), "{3, 1, 2}");
+        assert_eq!(log(set_from(BTreeSet::from([3, 1, 2]))), "{1, 2, 3}");
+        assert_eq!(log(set_from([1])), log(HashSet::from([1])));
+        assert_eq!(log(set_from(Vec::<i32>::new())), "{}");
+    }
+
+    #[test]
+    fn test_spaces_and_hardlines() {
+        assert_eq!(log(Doc::from("a") + spaces(3) + Doc::from("b")), "a   b");
+        assert_eq!(display_width(&spaces(3), &Printer::default()), 3);
+        assert_eq!(log(spaces(100)), " ".repeat(100));
+        assert_eq!(log(spaces(0)), "");
+
+        assert_eq!(
+            log(Doc::from("a") + hardlines(2) + Doc::from("b")),
+            "a\n\nb"
+        );
+        assert_eq!(
+            log(indent(Doc::from("a") + hardlines(1) + Doc::from("b"))),
+            "a\n  b"
+        );
+        assert_eq!(log(hardlines(0)), "");
+    }
+
+    #[test]
+    fn test_lines() {
+        let docs = || vec![Doc::from("a"), Doc::from(vec![1, 2]), Doc::from("c")];
+
+        assert_eq!(
+            log(indent(Doc::Hardline + lines(docs()))),
+            "\n  a\n  [1, 2]\n  c"
+        );
+        assert_eq!(
+            log(indent(Doc::Hardline + blank_lines(docs(), 1))),
+            "\n  a\n\n  [1, 2]\n\n  c"
+        );
+        assert_eq!(log(lines(Vec::<Doc>::new())), "");
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(
+            log(bytes(b"hello \"world\"\n".to_vec())),
+            r#""hello \"world\"\n""#
+        );
+        assert_eq!(log(bytes(vec![0xde, 0xad, 0xbe, 0xef])), "<de ad be ef>");
+
+        let binary: Vec<u8> = (0xe0..=0xff).collect();
+        assert_eq!(
+            log(bytes(binary)),
+            "<\n  e0 e1 e2 e3 e4 e5 e6 e7 e8 e9 ea eb ec ed ee ef \n  f0 f1 f2 f3 f4 f5 f6 f7 f8 f9 fa fb fc fd fe ff\n>"
+        );
+
+        // Still a list of numbers through the generic impl
+        assert_eq!(log(vec![104u8, 105]), "[104, 105]");
+    }
+
+    #[test]
+    fn test_lines_from() {
+        let log_lines = ["starting", "listening on :8080", "shutting down"];
+
+        assert_eq!(
+            log(indent(Doc::Hardline + lines_from(&log_lines))),
+            "\n  starting\n  listening on :8080\n  shutting down"
+        );
+        // Rather than as a list
+        assert!(log(&log_lines[..]).contains("starting, listening on :8080, "));
+    }
+
+    #[test]
+    fn test_number_wrappers() {
+        let value = std::num::Wrapping(255u8);
+        assert_eq!(log(value), "255");
+        assert_eq!(log(value + std::num::Wrapping(1)), "0");
+
+        assert_eq!(log(std::num::Saturating(-3i32)), "-3");
+    }
+
+    #[test]
+    fn test_std_enums() {
+        use std::cmp::Ordering;
+        use std::sync::atomic::Ordering as AtomicOrdering;
+
+        assert_eq!(log(Ordering::Less), "Less");
+        assert_eq!(log(Ordering::Equal), "Equal");
+        assert_eq!(log(Ordering::Greater), "Greater");
+        assert_eq!(log(1.cmp(&2)), "Less");
+
+        assert_eq!(log(AtomicOrdering::Relaxed), "Relaxed");
+        assert_eq!(log(AtomicOrdering::Release), "Release");
+        assert_eq!(log(AtomicOrdering::Acquire), "Acquire");
+        assert_eq!(log(AtomicOrdering::AcqRel), "AcqRel");
+        assert_eq!(log(AtomicOrdering::SeqCst), "SeqCst");
+
+        assert_eq!(log(f64::NAN.classify()), "Nan");
+        assert_eq!(log(f64::INFINITY.classify()), "Infinite");
+        assert_eq!(log(0.0f64.classify()), "Zero");
+        assert_eq!(log((f64::MIN_POSITIVE / 2.0).classify()), "Subnormal");
+        assert_eq!(log(1.0f64.classify()), "Normal");
+
+        assert_eq!(log(std::fmt::Alignment::Left), "Left");
+        assert_eq!(log(std::fmt::Alignment::Right), "Right");
+        assert_eq!(log(std::fmt::Alignment::Center), "Center");
+
+        assert_eq!(log(std::net::Shutdown::Read), "Read");
+        assert_eq!(log(std::net::Shutdown::Write), "Write");
+        assert_eq!(log(std::net::Shutdown::Both), "Both");
+
+        assert_eq!(
+            log(vec![Ordering::Less, Ordering::Greater]),
+            "[Less, Greater]"
+        );
+    }
+
+    #[test]
+    fn test_control_flow() {
+        use std::ops::{Bound, ControlFlow};
+
+        let flow: ControlFlow<&str, i32> = ControlFlow::Continue(1);
+        assert_eq!(log(flow), "Continue(1)");
+        let flow: ControlFlow<&str, i32> = ControlFlow::Break("done");
+        assert_eq!(log(flow), "Break(done)");
+        let flow: ControlFlow<i32, Vec<i32>> = ControlFlow::Continue(vec![1, 2]);
+        assert_eq!(log(flow.clone()), format!("{:?}", flow));
+
+        assert_eq!(log(Bound::Included(1)), "Included(1)");
+        assert_eq!(log(Bound::Excluded(2)), "Excluded(2)");
+        assert_eq!(log(Bound::<i32>::Unbounded), "Unbounded");
+    }
+
+    #[test]
+    fn test_system_time() {
+        use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+        assert_eq!(log(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+        let time = UNIX_EPOCH + Duration::from_secs(981_173_106);
+        assert_eq!(log(time), "2001-02-03T04:05:06Z");
+        assert_eq!(
+            log(time + Duration::from_millis(789)),
+            "2001-02-03T04:05:06.789Z"
+        );
+        assert_eq!(
+            log(time + Duration::from_nanos(1)),
+            "2001-02-03T04:05:06.000000001Z"
+        );
+        // A leap day
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_208_000);
+        assert_eq!(log(time), "2024-02-29T12:00:00Z");
+
+        // Before the epoch
+        let time: SystemTime = UNIX_EPOCH - Duration::from_millis(1500);
+        assert_eq!(log(time), "1969-12-31T23:59:58.500Z");
+        let time = UNIX_EPOCH - Duration::from_secs(86_400 * 365);
+        assert_eq!(log(time), "1969-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_smart_join_max() {
+        let printer = Printer::default();
+
+        let doc = smart_join_max(", ", (0..10).collect(), 3);
+
+        let pprint = printer.pprint(doc);
+        assert_eq!(pprint, "0, 1, 2, \n3, 4, 5, \n6, 7, 8, \n9");
+    }
+
+    #[derive(Debug)]
+    struct ChainError {
+        message: &'static str,
+        source: Option<Box<ChainError>>,
+    }
+
+    impl fmt::Display for ChainError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.message)
+        }
+    }
+
+    impl std::error::Error for ChainError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            self.source
+                .as_deref()
+                .map(|e| e as &(dyn std::error::Error + 'static))
+        }
+    }
+
+    #[test]
+    fn test_error_chain() {
+        let printer = Printer::default();
+
+        let err = ChainError {
+            message: "failed to load config",
+            source: Some(Box::new(ChainError {
+                message: "failed to read file",
+                source: Some(Box::new(ChainError {
+                    message: "permission denied",
+                    source: None,
+                })),
+            })),
+        };
+
+        let pprint = printer.pprint(error_chain(&err));
+        assert_eq!(
+            pprint,
+            "failed to load config\n  caused by: failed to read file\n    caused by: permission denied"
+        );
+
+        let err: &dyn std::error::Error = &err;
+        assert_eq!(printer.pprint(err), pprint);
+    }
+
+    #[test]
+    #[cfg(feature = "anyhow")]
+    fn test_anyhow() {
+        let printer = Printer::default();
+
+        let err = anyhow::anyhow!("permission denied")
+            .context("failed to read file")
+            .context("failed to load config");
+        assert_eq!(
+            printer.pprint(&err),
+            "failed to load config\n  caused by: failed to read file\n    caused by: permission denied"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "eyre")]
+    fn test_eyre() {
+        use eyre::WrapErr;
+
+        let printer = Printer::default();
+
+        let report = Err::<(), _>(std::io::Error::other("permission denied"))
+            .wrap_err("failed to read file")
+            .wrap_err("failed to load config")
+            .unwrap_err();
+        assert_eq!(
+            printer.pprint(&report),
+            "failed to load config\n  caused by: failed to read file\n    caused by: permission denied"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn test_chrono() {
+        let printer = Printer::default();
+
+        let datetime = chrono::DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        assert_eq!(printer.pprint(datetime), "2023-11-14T22:13:20+00:00");
+
+        let offset = chrono::FixedOffset::east_opt(2 * 3600).unwrap();
+        let datetime = datetime.with_timezone(&offset);
+        assert_eq!(printer.pprint(datetime), "2023-11-15T00:13:20+02:00");
+    }
+
+    #[test]
+    #[cfg(feature = "time")]
+    fn test_time() {
+        let printer = Printer::default();
+
+        let datetime = time::OffsetDateTime::from_unix_timestamp(1_700_000_000).unwrap();
+        assert_eq!(printer.pprint(datetime), "2023-11-14T22:13:20Z");
+    }
+
+    #[test]
+    #[cfg(feature = "serde_json")]
+    fn test_serde_json() {
+        use pprint::{bracket, key_value, Join};
+
+        let value = serde_json::json!({
+            "a": [1, 2],
+            "b": { "c": "d\"e" },
+        });
+
+        let expected = bracket(
+            "{",
+            vec![
+                key_value("\"a\"", vec![1, 2]),
+                key_value(
+                    "\"b\"",
+                    bracket(
+                        "{",
+                        vec![key_value("\"c\"", "\"d\\\"e\"")]
+                            .join(Doc::from(", ") + Doc::Hardline),
+                        "}",
+                    ),
+                ),
+            ]
+            .join(Doc::from(", ") + Doc::Hardline),
+            "}",
+        );
+
+        let doc = Doc::from(value);
+        assert_eq!(doc, expected);
+        assert_eq!(
+            Printer::default().pprint(doc),
+            "{\n  \"a\": [1, 2], \n  \"b\": {\"c\": \"d\\\"e\"}\n}"
+        );
+    }
+}
@@ -0,0 +1,44 @@
+#[cfg(test)]
+mod tests {
+    use pprint::{escape, EscapeKind, Printer};
+
+    const SPECIAL: &str = "it's \"quoted\", a\\b\nnext";
+
+    #[test]
+    fn test_escape_json() {
+        let printer = Printer::default();
+
+        let pprint = printer.pprint(escape(SPECIAL, EscapeKind::Json));
+        assert_eq!(pprint, r#""it's \"quoted\", a\\b\nnext""#);
+    }
+
+    #[test]
+    fn test_escape_shell() {
+        let printer = Printer::default();
+
+        let pprint = printer.pprint(escape(SPECIAL, EscapeKind::Shell));
+        assert_eq!(pprint, "'it'\\''s \"quoted\", a\\b\nnext'");
+
+        let pprint = printer.pprint(escape("safe/path-1.txt", EscapeKind::Shell));
+        assert_eq!(pprint, "safe/path-1.txt");
+    }
+
+    #[test]
+    fn test_escape_csv() {
+        let printer = Printer::default();
+
+        let pprint = printer.pprint(escape(SPECIAL, EscapeKind::Csv));
+        assert_eq!(pprint, "\"it's \"\"quoted\"\", a\\b\nnext\"");
+
+        let pprint = printer.pprint(escape("plain", EscapeKind::Csv));
+        assert_eq!(pprint, "plain");
+    }
+
+    #[test]
+    fn test_escape_sql() {
+        let printer = Printer::default();
+
+        let pprint = printer.pprint(escape(SPECIAL, EscapeKind::Sql));
+        assert_eq!(pprint, "'it''s \"quoted\", a\\b\nnext'");
+    }
+}
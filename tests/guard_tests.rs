@@ -0,0 +1,56 @@
+#[cfg(test)]
+mod tests {
+    use pprint::{pprint_guarded, CycleGuard, Doc, PrettyGuarded, Printer};
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct Node {
+        name: String,
+        children: Vec<Rc<RefCell<Node>>>,
+    }
+
+    impl PrettyGuarded for Node {
+        fn pretty_guarded(&self, guard: &mut CycleGuard) -> Doc<'static> {
+            self.name.pretty_guarded(guard)
+                + Doc::from(" -> ")
+                + self.children.pretty_guarded(guard)
+        }
+    }
+
+    fn node(name: &str) -> Rc<RefCell<Node>> {
+        Rc::new(RefCell::new(Node {
+            name: name.to_string(),
+            children: Vec::new(),
+        }))
+    }
+
+    #[test]
+    fn test_cycle_guard() {
+        let printer = Printer::default();
+
+        let a = node("a");
+        let b = node("b");
+        let c = node("c");
+        a.borrow_mut().children.push(b.clone());
+        b.borrow_mut().children.push(a.clone());
+
+        // Shared, but not cyclic, so printed in full both times
+        a.borrow_mut().children.push(c.clone());
+        b.borrow_mut().children.push(c.clone());
+
+        let pprint = pprint_guarded(&a, &printer);
+        assert_eq!(pprint, "a -> [b -> […, c -> []], c -> []]");
+
+        let printer = Printer {
+            ellipsis: "...".into(),
+            ..Printer::default()
+        };
+        let pprint = pprint_guarded(&a, &printer);
+        assert_eq!(pprint, "a -> [\n  b -> [..., c -> []], \n  c -> []\n]");
+        assert_eq!(printer.ellipsis_width(), 3);
+        assert_eq!(Printer::default().ellipsis_width(), 1);
+
+        // Break the cycle so that the nodes are dropped
+        b.borrow_mut().children.clear();
+    }
+}
@@ -0,0 +1,936 @@
+#[cfg(test)]
+mod tests {
+    use pprint::{
+        braces, brackets, concat, conditional_group, display_width, group, group_line,
+        if_break_root, indent, indent_by, join, list, parens, pprint, pprint_batch, pprint_cow,
+        pprint_lines, pprint_stats, smart_join, smart_join_max, table, BreakStyle, CollapseStyle,
+        Doc, Printer, PrinterError, RenderStats, WrapAlgo,
+    };
+    use std::borrow::Cow;
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn test_literal_concat() {
+        let printer = Printer::default();
+
+        let doc = concat(vec![
+            Doc::from("a"),
+            concat(vec!["b", "c"]),
+            Doc::Null,
+            indent(concat(vec![Doc::from("d"), Doc::Hardline, Doc::from("e")])),
+        ]);
+
+        let pprint = printer.pprint(doc);
+        assert_eq!(pprint, "abcd\n  e");
+    }
+
+    #[test]
+    fn test_break_long_text() {
+        let printer = |long_text_break| Printer {
+            max_width: 12,
+            break_long_text: true,
+            long_text_break,
+            ..Printer::default()
+        };
+        let url = "https://example.com/a/b/c";
+
+        let pprint = printer(BreakStyle::Word).pprint(indent(Doc::from("see ") + Doc::from(url)));
+        assert_eq!(pprint, "see https://example.com/a/b/c");
+        let pprint = printer(BreakStyle::Char).pprint(indent(Doc::from("see ") + Doc::from(url)));
+        assert_eq!(pprint, "see https://\n  example.co\n  m/a/b/c");
+
+        let text = "the quick brown fox jumps";
+        assert_eq!(
+            printer(BreakStyle::Word).pprint(text),
+            "the quick\nbrown fox\njumps"
+        );
+        assert_eq!(
+            printer(BreakStyle::Char).pprint(text),
+            "the quick br\nown fox jump\ns"
+        );
+        for style in [BreakStyle::Word, BreakStyle::Char] {
+            let doc = Doc::from(text);
+            assert_eq!(
+                pprint_cow(&doc, &printer(style)),
+                printer(style).pprint(text)
+            );
+        }
+
+        // Unless enabled, text is left to overflow
+        let printer = Printer {
+            max_width: 12,
+            ..Printer::default()
+        };
+        assert_eq!(printer.pprint(url), url);
+    }
+
+    #[test]
+    fn test_exact_widths() {
+        let exact = Printer {
+            exact_widths: true,
+            ..Printer::default()
+        };
+        let doc = |column: usize| {
+            Doc::from("x".repeat(column))
+                + group(concat(vec![
+                    Doc::from(vec![1.5, 2.25]),
+                    Doc::Softline,
+                    smart_join(", ", vec![0.5, 0.75]),
+                ]))
+        };
+
+        // The group's flat output is 20 columns wide, so it fits up to column 60
+        for column in 0..=60 {
+            let flat = format!("{}[1.5, 2.25]0.5, 0.75", "x".repeat(column));
+            assert_eq!(exact.pprint(doc(column)), flat);
+        }
+        assert_ne!(exact.pprint(doc(61)).lines().count(), 1);
+
+        // Whereas estimating the softline's width breaks the group much sooner
+        assert_ne!(Printer::default().pprint(doc(30)).lines().count(), 1);
+    }
+
+    #[test]
+    fn test_null_display() {
+        let doc = || join(", ", vec![Doc::from("a"), Doc::Null, Doc::from("b")]);
+
+        assert_eq!(Printer::default().pprint(doc()), "a, , b");
+
+        let printer = Printer {
+            null_display: Some("<null>".into()),
+            ..Printer::default()
+        };
+        assert_eq!(printer.pprint(doc()), "a, <null>, b");
+        assert_eq!(display_width(&doc(), &printer), 12);
+
+        // Only explicit nulls are displayed, not those the combinators add
+        assert_eq!(printer.pprint(Doc::from(vec![1, 2])), "[1, 2]");
+    }
+
+    #[test]
+    fn test_pprint_stats() {
+        let printer = Printer {
+            max_width: 10,
+            ..Printer::default()
+        };
+
+        let doc = Doc::from(vec![vec![1, 2], vec![3, 4, 5, 6]]);
+        let (pprint, stats) = pprint_stats(&doc, &printer);
+
+        assert_eq!(pprint, "[\n  [1, 2], \n  [\n    3, 4, \n    5, 6\n  ]\n]");
+        assert_eq!(
+            stats,
+            RenderStats {
+                lines: 7,
+                max_line_width: 10,
+                bytes: pprint.len(),
+                groups_broken: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_pprint_cow() {
+        let printer = Printer::default();
+
+        let s = "a string that's printed as is";
+        let doc = Doc::from(s);
+        let pprint = pprint_cow(&doc, &printer);
+        assert!(matches!(pprint, Cow::Borrowed(_)));
+        assert_eq!(pprint.as_ptr(), s.as_ptr());
+        assert_eq!(pprint, s);
+
+        let doc = Doc::from("café");
+        let printer = Printer {
+            ascii_only: true,
+            ..Printer::default()
+        };
+        assert_eq!(
+            pprint_cow(&doc, &printer),
+            Cow::<str>::Owned("caf\\u{e9}".to_string())
+        );
+
+        let doc = Doc::from("a") + Doc::from("b");
+        assert!(matches!(pprint_cow(&doc, &printer), Cow::Owned(_)));
+    }
+
+    #[test]
+    fn test_ascii_only() {
+        let printer = Printer {
+            ascii_only: true,
+            ..Printer::default()
+        };
+
+        let pprint = printer.pprint("café");
+        assert_eq!(pprint, "caf\\u{e9}");
+
+        let pprint = Printer::default().pprint("café");
+        assert_eq!(pprint, "café");
+    }
+
+    #[test]
+    fn test_map_spacing() {
+        let map = HashMap::from([("a", 1)]);
+
+        let compact = Printer {
+            space_after_colon: false,
+            ..Printer::default()
+        };
+        assert_eq!(compact.pprint(map.clone()), "{a:1}");
+
+        let spacious = Printer {
+            space_inside_brackets: true,
+            ..Printer::default()
+        };
+        assert_eq!(spacious.pprint(map.clone()), "{ a: 1 }");
+        assert_eq!(spacious.pprint(vec![1, 2]), "[ 1, 2 ]");
+
+        assert_eq!(Printer::default().pprint(map), "{a: 1}");
+    }
+
+    #[test]
+    fn test_broken_map_spacing() {
+        let map = HashMap::from([("a", 1), ("b", 2)]);
+
+        let spacious = Printer {
+            space_inside_brackets: true,
+            ..Printer::default()
+        };
+        let pprint = spacious.pprint(map);
+
+        // The padding is dropped when the map breaks across lines
+        assert!(pprint.starts_with("{\n  "));
+        assert!(pprint.ends_with("\n}"));
+        assert!(!pprint.contains("{ "));
+    }
+
+    #[test]
+    fn test_spacing_override() {
+        let doc = || {
+            braces(
+                concat(vec![
+                    Doc::from("a: "),
+                    brackets(join(", ", vec![1, 2]), Some(false)),
+                    Doc::from(", b: "),
+                    parens(Doc::from("x"), None),
+                ]),
+                Some(true),
+            )
+        };
+
+        assert_eq!(Printer::default().pprint(doc()), "{ a: [1, 2], b: (x) }");
+
+        let printer = Printer {
+            space_inside_brackets: true,
+            ..Printer::default()
+        };
+        assert_eq!(printer.pprint(doc()), "{ a: [1, 2], b: ( x ) }");
+
+        let printer = Printer {
+            max_width: 18,
+            ..Printer::default()
+        };
+        assert_eq!(printer.pprint(doc()), "{\n  a: [1, 2], b: (x)\n}");
+    }
+
+    #[test]
+    fn test_medium_threshold() {
+        let doc = concat(vec![Doc::from("aaaaa"), Doc::Mediumline, Doc::from("b")]);
+
+        let printer = Printer {
+            max_width: 20,
+            ..Printer::default()
+        };
+        assert_eq!(printer.pprint(doc.clone()), "aaaaab");
+
+        let printer = Printer {
+            max_width: 20,
+            medium_threshold: 0.2,
+            ..Printer::default()
+        };
+        assert_eq!(printer.pprint(doc), "aaaaa\nb");
+    }
+
+    #[test]
+    fn test_fits() {
+        let printer = Printer::default();
+
+        let doc = Doc::from(vec!["alpha", "beta", "gamma"]);
+        assert!(doc.fits(80, &printer));
+        assert!(!doc.fits(10, &printer));
+
+        assert!(!(Doc::from("a") + Doc::Hardline).fits(80, &printer));
+    }
+
+    #[test]
+    fn test_pprint_batch() {
+        let printer = Printer {
+            max_width: 20,
+            ..Printer::default()
+        };
+
+        let docs: Vec<_> = (0..100)
+            .map(|i| Doc::from((0..i).collect::<Vec<_>>()))
+            .collect();
+
+        let batch = pprint_batch(&docs, &printer);
+        let sequential: Vec<_> = docs.iter().map(|doc| pprint(doc, &printer)).collect();
+        assert_eq!(batch, sequential);
+    }
+
+    #[test]
+    fn test_compiled_doc() {
+        let mut map = HashMap::new();
+        map.insert("xs", Doc::from((0..30).collect::<Vec<_>>()));
+        map.insert("t", table(vec![vec!["a", "bb"], vec!["ccc", "d"]]));
+
+        let doc = Doc::from(vec![Doc::from(map), Doc::from(("a", "b"))]);
+        let compiled = doc.clone().compile();
+
+        for max_width in [10, 20, 40, 80, 120] {
+            let printer = Printer {
+                max_width,
+                ..Printer::default()
+            };
+            assert_eq!(compiled.render(&printer), printer.pprint(doc.clone()));
+            assert_eq!(compiled.render(&printer), printer.pprint(doc.clone()));
+        }
+    }
+
+    #[test]
+    fn test_smart_join_length() {
+        let printer = Printer::default();
+
+        // Fits within the justified width of 20, so it's measured, and printed, flat
+        let flat = smart_join(", ", (1..=7).collect());
+        assert_eq!(display_width(&flat, &printer), 19);
+        assert_eq!(
+            printer.pprint(vec![1, 2, 3, 4, 5, 6, 7]),
+            "[1, 2, 3, 4, 5, 6, 7]"
+        );
+
+        // Justification wraps it regardless of the page width, so its group breaks too
+        let wrapped = smart_join(", ", (1..=10).collect());
+        assert_eq!(display_width(&wrapped, &printer), 29 + 80);
+        assert_eq!(
+            printer.pprint((1..=10).collect::<Vec<_>>()),
+            "[\n  1, 2, 3, 4, 5, \n  6, 7, 8, 9, 10\n]"
+        );
+    }
+
+    #[test]
+    fn test_smart_join_degenerate() {
+        let printers = [1, 5, 80].into_iter().flat_map(|max_width| {
+            [WrapAlgo::MinRaggedness, WrapAlgo::Greedy].map(|wrap_algorithm| Printer {
+                max_width,
+                wrap_algorithm,
+                ..Printer::default()
+            })
+        });
+
+        for printer in printers {
+            for doc in [Doc::from("abcdefgh"), Doc::from(vec![1, 2, 3])] {
+                let expected = printer.pprint(doc.clone());
+
+                let joined = smart_join(", ", vec![doc.clone()]);
+                assert_eq!(
+                    display_width(&joined, &printer),
+                    display_width(&doc, &printer)
+                );
+                assert_eq!(printer.pprint(joined), expected);
+                assert_eq!(printer.pprint(smart_join_max(", ", vec![doc], 0)), expected);
+            }
+
+            let empty = smart_join(", ", Vec::<Doc>::new());
+            assert_eq!(display_width(&empty, &printer), 0);
+            assert_eq!(printer.pprint(empty), "");
+            assert_eq!(
+                printer.pprint(smart_join_max(", ", Vec::<Doc>::new(), 0)),
+                ""
+            );
+        }
+    }
+
+    #[test]
+    fn test_unbounded_width() {
+        let printer = Printer {
+            max_width: usize::MAX,
+            ..Printer::default()
+        };
+
+        let mut map = HashMap::new();
+        map.insert("xs", Doc::from((0..100).collect::<Vec<_>>()));
+
+        let doc = concat(vec![
+            Doc::from(map),
+            Doc::Hardline,
+            Doc::Softline,
+            Doc::Mediumline,
+            indent(Doc::Hardline + Doc::Hardline + Doc::from(("a", "b"))),
+            table(vec![vec!["a", "b"], vec!["c", "d"]]),
+        ]);
+
+        let pprint = printer.pprint(doc.clone());
+        assert!(pprint.starts_with("{xs: [0, 1, 2, "));
+        assert_eq!(doc.compile().render(&printer), pprint);
+    }
+
+    #[test]
+    fn test_integers() {
+        let printer = Printer::default();
+
+        assert_eq!(Doc::from(7u8), Doc::UInt(7));
+        assert_eq!(Doc::from(-7isize), Doc::Int(-7));
+
+        for doc in [
+            Doc::from(0),
+            Doc::from(-5),
+            Doc::from(1000usize),
+            Doc::from(i128::MIN),
+            Doc::from(u128::MAX),
+        ] {
+            let pprint = printer.pprint(doc.clone());
+            assert_eq!(display_width(&doc, &printer), pprint.len());
+        }
+        assert_eq!(printer.pprint(i128::MIN), i128::MIN.to_string());
+    }
+
+    #[test]
+    fn test_checked_printer() {
+        assert!(Printer::checked(80, 2, false, false).is_ok());
+        assert!(Printer::checked(1, 1, false, false).is_ok());
+        assert!(Printer::checked(4, 4, false, false).is_ok());
+
+        assert_eq!(
+            Printer::checked(0, 0, false, false).unwrap_err(),
+            PrinterError::ZeroWidth
+        );
+        assert_eq!(
+            Printer::checked(80, 0, false, false).unwrap_err(),
+            PrinterError::ZeroIndent
+        );
+        assert_eq!(
+            Printer::checked(4, 5, false, false).unwrap_err(),
+            PrinterError::IndentExceedsWidth {
+                indent: 5,
+                max_width: 4
+            }
+        );
+
+        for (medium_threshold, valid) in [(0.0, true), (1.0, true), (1.5, false), (f32::NAN, false)]
+        {
+            let printer = Printer {
+                medium_threshold,
+                ..Printer::default()
+            };
+            assert_eq!(printer.validate().is_ok(), valid);
+        }
+
+        assert!(Printer::TWO_SPACE.validate().is_ok());
+        assert_eq!(Printer::FOUR_SPACE.indent, 4);
+    }
+
+    #[test]
+    fn test_zero_indent() {
+        let printer = Printer {
+            max_width: 20,
+            indent: 0,
+            ..Printer::default()
+        };
+
+        let doc = Doc::from(HashMap::from([("a", (0..20).collect::<Vec<_>>())]));
+
+        let pprint = printer.pprint(doc);
+        assert!(pprint.lines().count() > 1);
+        for line in pprint.lines() {
+            assert!(!line.starts_with(char::is_whitespace), "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn test_display_width() {
+        let red = |s: &str| format!("\x1b[31m{}\x1b[0m", s);
+        let printer = Printer::default();
+
+        assert_eq!(display_width(&Doc::from(red("abc")), &printer), 3);
+        assert_eq!(display_width(&Doc::from("e\u{301}\u{200b}x"), &printer), 2);
+
+        // Styled items break exactly where their plain text would
+        let words = ["alpha", "beta", "gamma", "delta", "epsilon", "zeta", "eta"];
+        let plain: Vec<_> = words.iter().map(|w| w.to_string()).collect();
+        let styled: Vec<_> = words.iter().map(|w| red(w)).collect();
+        let zero_width: Vec<_> = words.iter().map(|w| format!("{}\u{200b}", w)).collect();
+        for max_width in [10, 20, 40, 80] {
+            let printer = Printer {
+                max_width,
+                ..Printer::default()
+            };
+            let expected = printer.pprint(plain.clone());
+            let pprint = printer
+                .pprint(styled.clone())
+                .replace("\x1b[31m", "")
+                .replace("\x1b[0m", "");
+            assert_eq!(pprint, expected);
+            let pprint = printer.pprint(zero_width.clone()).replace('\u{200b}', "");
+            assert_eq!(pprint, expected);
+        }
+    }
+
+    #[test]
+    fn test_empty_collection() {
+        let empties = || {
+            vec![
+                Doc::from(Vec::<i32>::new()),
+                Doc::from(HashMap::<i32, i32>::new()),
+                Doc::from(HashSet::<i32>::new()),
+                list("(", Vec::<i32>::new(), ", ", ")"),
+            ]
+        };
+
+        let printer = Printer::default();
+        let pprints: Vec<_> = empties()
+            .into_iter()
+            .map(|doc| printer.pprint(doc))
+            .collect();
+        assert_eq!(pprints, ["[]", "{}", "{}", "()"]);
+
+        let printer = Printer {
+            empty_collection: CollapseStyle::Expanded,
+            ..Printer::default()
+        };
+        let pprints: Vec<_> = empties()
+            .into_iter()
+            .map(|doc| printer.pprint(doc))
+            .collect();
+        assert_eq!(pprints, ["[\n]", "{\n}", "{\n}", "(\n)"]);
+
+        // Nested, the closing bracket lines up with the opening one's line
+        let doc = Doc::from(vec![Vec::<i32>::new()]);
+        assert_eq!(printer.pprint(doc), "[\n  [\n  ]\n]");
+    }
+
+    #[test]
+    fn test_embedded_newline() {
+        let printer = Printer {
+            max_width: 20,
+            ..Printer::default()
+        };
+
+        // Only the line after the break counts toward the list's fit
+        let doc = Doc::from("a rather long header\nxs = ") + Doc::from(vec![1, 2, 3]);
+        assert_eq!(printer.pprint(doc), "a rather long header\nxs = [1, 2, 3]");
+
+        let doc = concat(vec!["a rather long", " header\nxs = "]) + Doc::from(vec![1, 2, 3]);
+        assert_eq!(printer.pprint(doc), "a rather long header\nxs = [1, 2, 3]");
+
+        // ...and still breaks it once that line overflows
+        let doc = Doc::from("header\nxs = ") + Doc::from(vec![100, 200, 300, 400]);
+        assert_eq!(
+            printer.pprint(doc),
+            "header\nxs = [\n  100, 200, \n  300, 400\n]"
+        );
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let printer = Printer {
+            sort_keys: true,
+            ..Printer::default()
+        };
+        let pairs = [("b", 2), ("a1", 3), ("a", 1), ("c", 4)];
+
+        // Each map hashes with its own random state, so would otherwise iterate in
+        // its own order
+        for _ in 0..8 {
+            let map: HashMap<_, _> = pairs.into_iter().collect();
+            assert_eq!(
+                printer.pprint(map),
+                "{\n  a: 1, \n  a1: 3, \n  b: 2, \n  c: 4\n}"
+            );
+
+            let set: HashSet<_> = [5, 3, 10, 1].into_iter().collect();
+            let nested = vec![set.clone(), set];
+            assert_eq!(printer.pprint(nested), "[{1, 10, 3, 5}, {1, 10, 3, 5}]");
+        }
+    }
+
+    #[test]
+    fn test_pprint_lines() {
+        let printer = Printer {
+            max_width: 20,
+            ..Printer::default()
+        };
+        let docs = vec![
+            Doc::from(vec![vec![1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]),
+            Doc::from("embedded\nline breaks") + Doc::Hardline + Doc::from("too"),
+            table(vec![vec!["a", "b\nc"], vec!["d", "e"]]),
+            Doc::from(""),
+            Doc::Hardline,
+        ];
+
+        for doc in docs {
+            let lines: Vec<_> = printer
+                .pprint(doc.clone())
+                .split('\n')
+                .map(str::to_owned)
+                .collect();
+            assert_eq!(pprint_lines(&doc, &printer), lines);
+            assert_eq!(pprint_stats(&doc, &printer).1.lines, lines.len());
+        }
+    }
+
+    #[test]
+    fn test_if_break_root() {
+        let printer = Printer::default();
+        let inner = || {
+            group(concat(vec![
+                Doc::from("x"),
+                group_line("|"),
+                Doc::from("y"),
+                if_break_root("", " (root broke)"),
+            ]))
+        };
+
+        assert_eq!(
+            printer.pprint(group(Doc::from("fits: ") + inner())),
+            "fits: x|y"
+        );
+
+        // The outer group breaks, but the inner one, on a line of its own, fits:
+        // its group line stays flat, while the root-aware branch breaks
+        let doc = group(concat(vec![Doc::from("header"), Doc::Hardline, inner()]));
+        assert_eq!(printer.pprint(doc), "\nheader\nx|y (root broke)\n");
+
+        // Outside of any group, the page counts as broken
+        assert_eq!(printer.pprint(if_break_root("flat", "broken")), "broken");
+    }
+
+    #[test]
+    fn test_indent_by() {
+        let printer = Printer::default();
+
+        let doc = concat(vec![
+            Doc::from("two:"),
+            indent(Doc::Hardline + Doc::from("a")),
+            Doc::Hardline,
+            Doc::from("four:"),
+            indent_by(
+                4,
+                Doc::Hardline + Doc::from("b") + indent(Doc::Hardline + Doc::from("c")),
+            ),
+        ]);
+        assert_eq!(printer.pprint(doc), "two:\n  a\nfour:\n    b\n      c");
+
+        // A broken group closes back at the column its indent started from
+        let items = || join(Doc::from(",") + Doc::Hardline, vec![1, 2]);
+        let doc = concat(vec![
+            indent_by(
+                4,
+                concat(vec![Doc::from("["), group(items()), Doc::from("]")]),
+            ),
+            Doc::Hardline,
+            indent(concat(vec![Doc::from("["), group(items()), Doc::from("]")])),
+        ]);
+        assert_eq!(printer.pprint(doc), "[\n    1,\n    2\n]\n[\n  1,\n  2\n]");
+    }
+
+    #[test]
+    fn test_prologue_epilogue() {
+        let plain = Printer {
+            max_width: 30,
+            ..Printer::default()
+        };
+        let printer = Printer {
+            prologue: Some(Cow::Borrowed("\u{feff}// generated: ")),
+            epilogue: Some(Cow::Borrowed("\n")),
+            ..plain.clone()
+        };
+
+        // The prologue doesn't count towards the width of the line it's written on
+        let doc = Doc::from((1..=20).collect::<Vec<_>>());
+        assert_eq!(
+            pprint(&doc, &printer),
+            format!("\u{feff}// generated: {}\n", pprint(&doc, &plain))
+        );
+
+        let printer = Printer {
+            prologue: Some(Cow::Borrowed("\u{feff}")),
+            epilogue: None,
+            ..printer
+        };
+        let doc = Doc::from("text");
+        assert_eq!(pprint_cow(&doc, &printer), "\u{feff}text");
+
+        // Every way of printing adds them, after numbering the document's lines
+        let printer = Printer {
+            prologue: Some(Cow::Borrowed("HDR\n")),
+            line_numbers: true,
+            ..Printer::default()
+        };
+        let doc = Doc::from("a") + Doc::Hardline + Doc::from("b");
+        assert_eq!(pprint(&doc, &printer), "HDR\n1 | a\n2 | b");
+        assert_eq!(pprint_lines(&doc, &printer), ["HDR", "1 | a", "2 | b"]);
+        let (output, stats) = pprint_stats(&doc, &printer);
+        assert_eq!(output, pprint(&doc, &printer));
+        assert_eq!(stats.lines, 3);
+    }
+
+    #[test]
+    fn test_max_output_bytes() {
+        let mut doc = Doc::from(vec![0]);
+        for i in 1..12 {
+            doc = Doc::from(vec![Doc::from(i), doc]);
+        }
+        let full = pprint(&doc, &Printer::default());
+
+        let printer = Printer {
+            max_output_bytes: Some(30),
+            ..Printer::default()
+        };
+        let output = pprint(&doc, &printer);
+        assert_eq!(output, "[\n  11, \n  [10, [9, [8, [7, [6… (truncated)");
+        assert!(full.starts_with(&output[..30]));
+        assert_eq!(pprint_lines(&doc, &printer).join("\n"), output);
+
+        let printer = Printer {
+            ellipsis: Cow::Borrowed("..."),
+            max_output_bytes: Some(3),
+            ..printer
+        };
+        assert_eq!(pprint(&doc, &printer), "[\n ... (truncated)");
+
+        let printer = Printer {
+            max_output_bytes: Some(full.len()),
+            ..printer
+        };
+        assert_eq!(pprint(&doc, &printer), full);
+
+        let printer = Printer {
+            max_output_bytes: Some(8),
+            ..Printer::default()
+        };
+        assert_eq!(
+            pprint_cow(&Doc::from("a long string"), &printer),
+            "a long s… (truncated)"
+        );
+
+        // Each cell is cut short only as part of the table as a whole
+        let cells = table(vec![vec![
+            Doc::from("x".repeat(20)),
+            Doc::from("y".repeat(20)),
+        ]]);
+        let output = pprint(&cells, &printer);
+        assert_eq!(output, "xxxxxxxx… (truncated)");
+
+        // The gutter and enclosure count towards the limit
+        let printer = Printer {
+            max_output_bytes: Some(30),
+            line_numbers: true,
+            prologue: Some(Cow::Borrowed(">> ")),
+            ..Printer::default()
+        };
+        let numbered = pprint(
+            &doc,
+            &Printer {
+                max_output_bytes: None,
+                ..printer.clone()
+            },
+        );
+        let output = pprint(&doc, &printer);
+        assert_eq!(output, ">> 1 | [\n2 |   11, \n3 |   [10,… (truncated)");
+        assert_eq!(output.len(), 30 + "… (truncated)".len());
+        assert!(numbered.starts_with(&output[..30]));
+    }
+
+    #[test]
+    fn test_line_numbers() {
+        let printer = Printer {
+            line_numbers: true,
+            ..Printer::default()
+        };
+
+        let lines: Vec<_> = (1..=12).map(|i| format!("line {}", i)).collect();
+        let mut docs: Vec<Doc> = lines.iter().map(Doc::from).collect();
+        docs[4] = Doc::from("");
+        let doc = join(Doc::Hardline, docs);
+
+        let expected = [
+            " 1 | line 1",
+            " 2 | line 2",
+            " 3 | line 3",
+            " 4 | line 4",
+            " 5 |",
+            " 6 | line 6",
+            " 7 | line 7",
+            " 8 | line 8",
+            " 9 | line 9",
+            "10 | line 10",
+            "11 | line 11",
+            "12 | line 12",
+        ];
+        assert_eq!(pprint(&doc, &printer), expected.join("\n"));
+        assert_eq!(pprint_lines(&doc, &printer), expected);
+        assert_eq!(pprint_stats(&doc, &printer).0, expected.join("\n"));
+
+        assert_eq!(pprint(&Doc::from("one"), &printer), "1 | one");
+        assert_eq!(pprint_cow(&Doc::from("one"), &printer), "1 | one");
+    }
+
+    #[test]
+    fn test_indent_char() {
+        let printer = Printer {
+            max_width: 10,
+            break_long_text: true,
+            indent_char: '\u{2003}',
+            ..Printer::default()
+        };
+
+        // Each em space is a single column, though three bytes, so the text just fits
+        let doc = Doc::from("a:") + indent(Doc::Hardline + Doc::from("aaaa bbb"));
+        let (output, stats) = pprint_stats(&doc, &printer);
+        assert_eq!(output, "a:\n\u{2003}\u{2003}aaaa bbb");
+        assert_eq!(stats.max_line_width, 10);
+
+        let doc = Doc::from("a:") + indent(Doc::Hardline + Doc::from("aaaa bbbb"));
+        assert_eq!(
+            pprint(&doc, &printer),
+            "a:\n\u{2003}\u{2003}aaaa\n\u{2003}\u{2003}bbbb"
+        );
+
+        let printer = Printer {
+            use_tabs: true,
+            ..printer
+        };
+        assert_eq!(pprint(&doc, &printer), "a:\n\t\taaaa\n\t\tbbbb");
+    }
+
+    #[test]
+    fn test_collapse_adjacent_lines() {
+        let printer = Printer {
+            collapse_adjacent_lines: true,
+            ..Printer::default()
+        };
+
+        let doc = concat(vec![
+            Doc::from("a"),
+            Doc::Hardline,
+            Doc::Hardline,
+            Doc::from("b"),
+        ]);
+        assert_eq!(pprint(&doc, &Printer::default()), "a\n\nb");
+        assert_eq!(pprint(&doc, &printer), "a\nb");
+
+        // The last of the breaks wins, along with its indentation
+        let doc = Doc::from("a:")
+            + indent(concat(vec![
+                Doc::Line,
+                Doc::Hardline,
+                Doc::from("b"),
+                Doc::Hardline,
+            ]))
+            + Doc::Hardline
+            + Doc::from("c");
+        assert_eq!(pprint(&doc, &printer), "a:\n  b\nc");
+
+        let (output, stats) = pprint_stats(&doc, &printer);
+        assert_eq!(stats.lines, output.lines().count());
+    }
+
+    #[test]
+    #[cfg(feature = "ropey")]
+    fn test_pprint_rope() {
+        let printer = Printer {
+            max_width: 10,
+            ..Printer::default()
+        };
+        let doc = Doc::from(vec!["alpha", "beta", "gamma"]);
+
+        let mut rope = pprint::pprint_rope(&doc, &printer);
+        assert_eq!(rope.to_string(), pprint(&doc, &printer));
+        assert_eq!(rope.len_lines(), pprint_lines(&doc, &printer).len());
+
+        rope.insert(0, "let x = ");
+        assert!(rope.line(0).to_string().starts_with("let x = ["));
+    }
+
+    #[test]
+    fn test_extremely_wide_line() {
+        let printer = Printer {
+            max_width: usize::MAX,
+            ..Printer::default()
+        };
+
+        let wide = "x".repeat(1 << 20);
+        let line = concat(vec![
+            Doc::from(wide.as_str()),
+            indent_by(usize::MAX, group(Doc::from(vec![1, 2, 3]))),
+            Doc::from(wide.as_str()),
+            smart_join(", ", vec![Doc::from(wide.as_str()), Doc::from(u128::MAX)]),
+            table(vec![vec![Doc::from(wide.as_str()), Doc::from("cell")]]),
+        ]);
+
+        let (output, stats) = pprint_stats(&line, &printer);
+        assert_eq!(stats.lines, 1);
+        assert_eq!(stats.max_line_width, output.len());
+        assert!(output.ends_with(&format!("{}, {}{} cell", wide, u128::MAX, wide)));
+    }
+
+    #[test]
+    fn test_from_columns() {
+        let printer = Printer::from_columns(Some("120"));
+        assert_eq!(printer.max_width, 120);
+        assert_eq!(printer.indent, Printer::default().indent);
+
+        // Unusable widths fall back to the terminal's, or the default
+        #[cfg(not(feature = "terminal_size"))]
+        for columns in [Some("0"), Some("wide"), Some(""), None] {
+            assert_eq!(
+                Printer::from_columns(columns).max_width,
+                Printer::default().max_width
+            );
+        }
+    }
+
+    #[test]
+    fn test_conditional_group() {
+        let printer = Printer {
+            max_width: 20,
+            indent: 4,
+            ..Printer::default()
+        };
+        let entries = || concat(vec![Doc::from("a: 1,"), Doc::Hardline, Doc::from("bb: 2")]);
+        let call = || {
+            conditional_group(vec![
+                concat(vec![Doc::from("foo({a: 1, bb: 2, ccc: 3})")]),
+                // Inherently multi-line, but its first line fits
+                concat(vec![
+                    Doc::from("foo({"),
+                    indent(concat(vec![Doc::Hardline, entries()])),
+                    Doc::Hardline,
+                    Doc::from("})"),
+                ]),
+                concat(vec![
+                    Doc::from("foo("),
+                    indent(concat(vec![Doc::Hardline, Doc::from("{a: 1, bb: 2}")])),
+                    Doc::Hardline,
+                    Doc::from(")"),
+                ]),
+            ])
+        };
+
+        assert_eq!(pprint(&call(), &printer), "foo({\n    a: 1,\n    bb: 2\n})");
+
+        let wide = Printer {
+            max_width: 40,
+            ..printer.clone()
+        };
+        assert_eq!(pprint(&call(), &wide), "foo({a: 1, bb: 2, ccc: 3})");
+
+        // Once no first line fits, the most expanded alternative is printed
+        let doc = concat(vec![Doc::from("x".repeat(17)), call()]);
+        assert_eq!(
+            pprint(&doc, &printer),
+            format!("{}foo(\n    {{a: 1, bb: 2}}\n)", "x".repeat(17))
+        );
+    }
+}
@@ -0,0 +1,12 @@
+#[cfg(test)]
+#[cfg(feature = "insta")]
+mod tests {
+    use pprint::{assert_doc_snapshot, Doc};
+
+    #[test]
+    fn test_doc_snapshot() {
+        let doc = Doc::from(vec![(0..30).collect::<Vec<_>>(), vec![1, 2, 3]]);
+
+        assert_doc_snapshot!(doc);
+    }
+}
@@ -0,0 +1,11 @@
+use pprint::Pretty;
+
+#[derive(Pretty)]
+struct Point(#[pprint(rename = "x")] i32, i32);
+
+#[derive(Pretty)]
+enum Shape {
+    Circle(#[pprint(rename = "radius")] u32),
+}
+
+fn main() {}
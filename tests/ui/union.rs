@@ -0,0 +1,9 @@
+use pprint::Pretty;
+
+#[derive(Pretty)]
+union Bits {
+    int: u32,
+    float: f32,
+}
+
+fn main() {}
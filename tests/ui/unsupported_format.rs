@@ -0,0 +1,9 @@
+use pprint::Pretty;
+
+#[derive(Pretty)]
+#[pprint(format = "toml")]
+struct Config {
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,26 @@
+#[cfg(test)]
+mod tests {
+    use pprint::{line_breaks, text_width};
+
+    #[test]
+    fn test_line_breaks() {
+        // A greedy fill to a width of 19 would give the ragged
+        // "the quick brown fox" / "jumps over the lazy" / "dog"
+        let words = "the quick brown fox jumps over the lazy dog";
+        let lengths: Vec<_> = words.split(' ').map(str::len).collect();
+
+        let breaks = line_breaks(1, &lengths, 19);
+        assert_eq!(breaks, vec![3, 6]);
+
+        assert_eq!(line_breaks(1, &[], 16), Vec::<usize>::new());
+        assert_eq!(line_breaks(1, &[3, 3], 16), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_text_width() {
+        assert_eq!(text_width("plain"), 5);
+        assert_eq!(text_width("\x1b[1;32mgreen\x1b[0m"), 5);
+        assert_eq!(text_width("cafe\u{301}"), 4);
+        assert_eq!(text_width("\u{feff}zero\u{200d}width"), 9);
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(test)]
+mod tests {
+    use pprint::{
+        display_width, greedy_line_breaks, group, join, line_breaks, smart_join, Doc, Indent,
+        Printer, WrapAlgo,
+    };
+    use proptest::prelude::*;
+
+    /// Documents built only from short words and breakable primitives, which
+    /// should therefore always be able to fit within the page width.
+    fn breakable_doc() -> impl Strategy<Value = Doc<'static>> {
+        let leaf = "[a-z]{1,8}".prop_map(Doc::from);
+        leaf.prop_recursive(5, 128, 12, |inner| {
+            prop_oneof![
+                prop::collection::vec(inner.clone(), 1..12)
+                    .prop_map(|docs| group(smart_join(", ", docs))),
+                prop::collection::vec(inner.clone(), 1..12)
+                    .prop_map(|docs| smart_join(", ", docs).indent()),
+                prop::collection::vec(inner, 1..4).prop_map(|docs| join(Doc::Line, docs)),
+            ]
+        })
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(2000))]
+        #[test]
+        fn test_breakable_docs_fit(doc in breakable_doc(), max_width in 20usize..100) {
+            let printer = Printer { max_width, ..Printer::default() };
+            let pprint = printer.pprint(doc.clone());
+
+            for line in pprint.lines() {
+                prop_assert!(
+                    line.len() <= max_width,
+                    "{:?} exceeds {} columns in {}",
+                    line,
+                    max_width,
+                    doc.to_sexpr()
+                );
+            }
+        }
+
+        #[test]
+        fn test_greedy_breakable_docs_fit(doc in breakable_doc(), max_width in 20usize..100) {
+            let printer = Printer {
+                max_width,
+                wrap_algorithm: WrapAlgo::Greedy,
+                ..Printer::default()
+            };
+            let pprint = printer.pprint(doc.clone());
+
+            for line in pprint.lines() {
+                prop_assert!(
+                    line.len() <= max_width,
+                    "{:?} exceeds {} columns in {}",
+                    line,
+                    max_width,
+                    doc.to_sexpr()
+                );
+            }
+        }
+
+        #[test]
+        fn test_integer_join_width(ints in prop::collection::vec(any::<i64>(), 0..50)) {
+            let printer = Printer::default();
+            let doc = join(", ", ints.iter().map(|&n| Doc::from(n)).collect());
+            let flat = ints.iter().map(i64::to_string).collect::<Vec<_>>().join(", ");
+
+            prop_assert_eq!(display_width(&doc, &printer), flat.len());
+        }
+
+        // Smart joins that fit on a single justified line skip the line breaking entirely
+        #[test]
+        fn test_fitting_lines_never_break(
+            lengths in prop::collection::vec(0usize..10, 0..8),
+            sep_length in 0usize..3,
+            slack in 1usize..20,
+        ) {
+            let total = lengths.iter().sum::<usize>()
+                + sep_length * lengths.len().saturating_sub(1);
+            let max_width = total + slack;
+
+            prop_assert!(line_breaks(sep_length, &lengths, max_width).is_empty());
+            prop_assert!(greedy_line_breaks(sep_length, &lengths, max_width).is_empty());
+        }
+    }
+}